@@ -1,8 +1,5 @@
 /*
- * TODO: show if tab is loading.
  * TODO: favicon.
- * TODO: loading errors.
- * TODO: close tab.
  */
 
 extern crate gdk;
@@ -20,6 +17,7 @@ use gtk::{
     ButtonExt,
     Cast,
     ContainerExt,
+    Continue,
     Entry,
     EntryExt,
     GtkWindowExt,
@@ -40,7 +38,7 @@ use gtk::{
     WindowType,
 };
 use gtk::Orientation::Vertical;
-use servo_gtk::WebView;
+use servo_gtk::{LoadEvent, WebView, WebViewBuilder};
 use servo_gtk::view::View;
 
 macro_rules! with_tab {
@@ -80,15 +78,21 @@ impl App {
         app
     }
 
-    fn close_tab(tabs: &Notebook, webviews: &WebViews, widgets: &Widgets) {
-        let page = tabs.get_current_page();
-        tabs.remove_page(page);
-        if let Some(page) = page {
-            let webview = webviews.borrow_mut().remove(page as usize);
+    fn close_tab(tabs: &Notebook, webviews: &WebViews) {
+        // The tab is removed once the page is closed, see webview_events().
+        with_tab!(tabs, webviews, |webview| {
+            webview.close();
+        });
+    }
+
+    fn remove_tab(tabs: &Notebook, webviews: &WebViews, widgets: &Widgets, view: &View) {
+        let position = webviews.borrow().iter().position(|webview| webview.view() == *view);
+        if let Some(position) = position {
+            webviews.borrow_mut().remove(position);
+            tabs.remove_page(Some(position as u32));
             if webviews.borrow().is_empty() {
                 Self::new_tab(tabs, webviews, widgets);
             }
-            webview.close();
         }
     }
 
@@ -109,7 +113,7 @@ impl App {
                     key::Page_Down => tabs.next_page(),
                     key::Page_Up => tabs.prev_page(),
                     key::t => Self::new_tab(&tabs, &webviews, &widgets),
-                    key::w => Self::close_tab(&tabs, &webviews, &widgets),
+                    key::w => Self::close_tab(&tabs, &webviews),
                     _ => (),
                 }
             }
@@ -133,7 +137,9 @@ impl App {
                     format!("http://{}", url)
                 };
             with_tab!(tabs, webviews, |webview| {
-                webview.load(&url);
+                if let Err(error) = webview.load(&url) {
+                    eprintln!("Cannot load {}: {}", url, error);
+                }
             });
         });
 
@@ -188,17 +194,20 @@ impl App {
         });
     }
 
-    fn new_tab(tabs: &Notebook, webviews: &WebViews, widgets: &Widgets) {
-        let webview = WebView::new();
+    fn add_tab(tabs: &Notebook, webviews: &WebViews, widgets: &Widgets, webview: WebView) {
         let view = webview.view();
         view.set_vexpand(true);
         tabs.add(&view);
         tabs.set_tab_label_text(&view, "New tab");
         view.show();
-        Self::webview_events(&widgets, &webview);
+        Self::webview_events(&widgets, webviews, &webview);
         webviews.borrow_mut().push(webview);
     }
 
+    fn new_tab(tabs: &Notebook, webviews: &WebViews, widgets: &Widgets) {
+        Self::add_tab(tabs, webviews, widgets, WebView::new());
+    }
+
     fn view() -> App {
         let window = Window::new(WindowType::Toplevel);
         window.set_size_request(800, 600);
@@ -252,17 +261,57 @@ impl App {
             window,
         });
 
-        Self::webview_events(&widgets, &webview);
+        let webviews = Rc::new(RefCell::new(vec![]));
+        Self::webview_events(&widgets, &webviews, &webview);
+        webviews.borrow_mut().push(webview);
 
         let app = App {
-            webviews: Rc::new(RefCell::new(vec![webview])),
+            webviews,
             widgets: widgets.clone(),
         };
 
         app
     }
 
-    fn webview_events(widgets: &Widgets, webview: &WebView) {
+    fn webview_events(widgets: &Widgets, webviews: &WebViews, webview: &WebView) {
+        {
+            let view = webview.view();
+            let webviews = webviews.clone();
+            let widgets = widgets.clone();
+            webview.connect_destroyed(move || {
+                // Remove the tab once the current callbacks are done with the web view.
+                let view = view.clone();
+                let webviews = webviews.clone();
+                let widgets = widgets.clone();
+                gtk::idle_add(move || {
+                    Self::remove_tab(&widgets.tabs, &webviews, &widgets, &view);
+                    Continue(false)
+                });
+            });
+        }
+
+        {
+            let context = webview.get_context();
+            let webviews = webviews.clone();
+            let widgets = widgets.clone();
+            webview.connect_create(move |_action| {
+                let new_webview = WebViewBuilder::new()
+                    .context(&context)
+                    .url("about:blank")
+                    .build()
+                    .ok()?;
+                // Open it in a background tab, once the current callbacks are done with the tabs.
+                let tab_webview = new_webview.clone();
+                let webviews = webviews.clone();
+                let widgets = widgets.clone();
+                gtk::idle_add(move || {
+                    Self::add_tab(&widgets.tabs, &webviews, &widgets, tab_webview.clone());
+                    Continue(false)
+                });
+                Some(new_webview)
+            });
+        }
+
         {
             let tabs = widgets.tabs.clone();
             let window = widgets.window.clone();
@@ -280,6 +329,25 @@ impl App {
             });
         }
 
+        {
+            let tabs = widgets.tabs.clone();
+            let view = webview.view();
+            webview.connect_load_changed(move |event| {
+                if event == LoadEvent::Started {
+                    tabs.set_tab_label_text(&view, "Loading...");
+                }
+            });
+        }
+
+        {
+            let tabs = widgets.tabs.clone();
+            let view = webview.view();
+            webview.connect_load_failed(move |url, error| {
+                eprintln!("Cannot load {}: {:?}", url, error);
+                tabs.set_tab_label_text(&view, "Problem loading page");
+            });
+        }
+
         {
             let view = webview.clone();
             webview.view().connect_scroll_event(move |_, event| {
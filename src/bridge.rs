@@ -0,0 +1,297 @@
+/*
+ * Servo does not give the embedder a way to talk with the pages, so the pages we control talk to us
+ * with alert() messages starting with PREFIX. Those messages are never shown to the user.
+ *
+ * Any page can call alert() with PREFIX, so the messages that must not be forged also carry a
+ * random nonce the pages cannot read. The error pages show theirs, so each is only accepted once.
+ */
+
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+
+use serde_json::{self, Value};
+
+use convert;
+
+const PREFIX: &str = "\u{0}servo-gtk\u{0}";
+
+/// Evaluates the script {source} in the global scope and reports its result, serialized as JSON, or
+/// the exception it threw. The reports carry {token}, only known by this run of the script.
+const RUN_SCRIPT: &str = r#"(function() {
+    var result;
+    try {
+        result = JSON.stringify((0, eval)({source}));
+    }
+    catch (error) {
+        var name = error instanceof Error ? error.name : typeof error;
+        var message = error instanceof Error ? error.message : String(error);
+        alert("\u0000servo-gtk\u0000script-error\n{id}\n{token}\n" + name + "\n" + message);
+        return;
+    }
+    alert("\u0000servo-gtk\u0000script-result\n{id}\n{token}\n" + (result === undefined ? "null" : result));
+})()"#;
+
+/// Defines window.servoGtk.postMessage(), sending a message to the handler `name` and returning a
+/// promise of the reply. The messages carry {token}, only known by the document.
+const MESSAGE_SCRIPT: &str = r#"(function() {
+    if (window.servoGtk) {
+        return;
+    }
+    var nextId = 0;
+    var pending = {};
+    window.servoGtk = {
+        postMessage: function(name, message) {
+            var id = nextId++;
+            return new Promise(function(resolve, reject) {
+                pending[id] = { resolve: resolve, reject: reject };
+                alert("\u0000servo-gtk\u0000message\n{token}\n" + id + "\n" + name + "\n" +
+                    JSON.stringify(message === undefined ? null : message));
+            });
+        },
+        _reply: function(id, reply, error) {
+            var promise = pending[id];
+            delete pending[id];
+            if (promise && error) {
+                promise.reject(new Error(error));
+            }
+            else if (promise) {
+                promise.resolve(reply);
+            }
+        },
+    };
+})()"#;
+
+/// Dispatches the events of the pointer leaving the elements it hovers: `mouseout` on the deepest
+/// one, then `mouseleave` on each of them.
+pub const POINTER_LEAVE_SCRIPT: &str = r#"(function() {
+    var hovered = document.querySelectorAll(":hover");
+    if (hovered.length === 0) {
+        return;
+    }
+    var init = { bubbles: true, cancelable: true, view: window, relatedTarget: null };
+    hovered[hovered.length - 1].dispatchEvent(new MouseEvent("mouseout", init));
+    for (var i = hovered.length - 1; i >= 0; i--) {
+        hovered[i].dispatchEvent(new MouseEvent("mouseleave", { view: window, relatedTarget: null }));
+    }
+})()"#;
+
+/// Appended to the error pages: Servo replaces ${reason} by the error message.
+const ERROR_PAGE_SCRIPT: &str = r#"
+<script type="text/plain" id="servo-gtk-reason">${reason}</script>
+<script>
+    alert("\u0000servo-gtk\u0000load-failed\n{nonce}\n" + location.href + "\n" +
+        document.getElementById("servo-gtk-reason").textContent);
+</script>
+"#;
+
+#[derive(Debug, PartialEq)]
+pub enum Message {
+    HandlerMessage {
+        token: String,
+        id: u64,
+        name: String,
+        message: Value,
+    },
+    LoadFailed {
+        nonce: String,
+        url: String,
+        reason: String,
+    },
+    ScriptError {
+        id: u64,
+        token: String,
+        name: String,
+        message: String,
+    },
+    ScriptResult {
+        id: u64,
+        token: String,
+        result: Value,
+    },
+}
+
+/// Make the script appended to the error pages, reporting the failure with `nonce`.
+pub fn error_page_script(nonce: &str) -> String {
+    ERROR_PAGE_SCRIPT.replace("{nonce}", nonce)
+}
+
+/// Make a `javascript:` URL running `source` in the page without replacing the document.
+pub fn javascript_url(source: &str) -> String {
+    // Servo percent-decodes the URL before running it, and the URL parser strips the newlines.
+    format!("javascript:void({})", convert::percent_encode(source.as_bytes()))
+}
+
+/// Make the script defining `window.servoGtk`, sending its messages with `token`.
+pub fn message_script(token: &str) -> String {
+    MESSAGE_SCRIPT.replace("{token}", token)
+}
+
+/// Make a random nonce: the keys of `RandomState` come from the random source of the system.
+pub fn nonce() -> String {
+    let state = RandomState::new();
+    let mut first = state.build_hasher();
+    first.write_u8(0);
+    let mut second = state.build_hasher();
+    second.write_u8(1);
+    format!("{:016x}{:016x}", first.finish(), second.finish())
+}
+
+/// Make the script resolving the promise of the message `id` with `reply`, or rejecting it with
+/// `error`.
+pub fn reply(id: u64, reply: Option<Value>, error: Option<&str>) -> String {
+    let reply = reply.unwrap_or(Value::Null);
+    let error = error.map(|error| Value::String(error.to_string())).unwrap_or(Value::Null);
+    format!("window.servoGtk._reply({}, {}, {})", id, reply, error)
+}
+
+/// Make the script reporting the result of running `source` as the message `id`, with `token`.
+pub fn run_script(id: u64, token: &str, source: &str) -> String {
+    let source = serde_json::to_string(source).expect("strings serialize to JSON");
+    RUN_SCRIPT
+        .replace("{id}", &id.to_string())
+        .replace("{token}", token)
+        .replace("{source}", &source)
+}
+
+/// Parse an alert() message, returning `None` if it is a regular alert from the page.
+pub fn parse(text: &str) -> Option<Message> {
+    if !text.starts_with(PREFIX) {
+        return None;
+    }
+    let mut fields = text[PREFIX.len()..].splitn(3, '\n');
+    match fields.next()? {
+        "load-failed" => {
+            let nonce = fields.next()?.to_string();
+            let mut failure = fields.next()?.splitn(2, '\n');
+            let url = failure.next()?.to_string();
+            let reason = failure.next().unwrap_or_default().to_string();
+            Some(Message::LoadFailed {
+                nonce,
+                url,
+                reason,
+            })
+        },
+        "message" => {
+            let token = fields.next()?.to_string();
+            let mut message = fields.next()?.splitn(3, '\n');
+            let id = message.next()?.parse().ok()?;
+            let name = message.next()?.to_string();
+            let message = serde_json::from_str(message.next()?).unwrap_or(Value::Null);
+            Some(Message::HandlerMessage {
+                token,
+                id,
+                name,
+                message,
+            })
+        },
+        "script-error" => {
+            let id = fields.next()?.parse().ok()?;
+            let mut error = fields.next()?.splitn(3, '\n');
+            let token = error.next()?.to_string();
+            let name = error.next()?.to_string();
+            let message = error.next().unwrap_or_default().to_string();
+            Some(Message::ScriptError {
+                id,
+                token,
+                name,
+                message,
+            })
+        },
+        "script-result" => {
+            let id = fields.next()?.parse().ok()?;
+            let mut result = fields.next()?.splitn(2, '\n');
+            let token = result.next()?.to_string();
+            let result = serde_json::from_str(result.next()?).unwrap_or(Value::Null);
+            Some(Message::ScriptResult {
+                id,
+                token,
+                result,
+            })
+        },
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{self, Value};
+
+    use super::{Message, PREFIX, nonce, parse};
+
+    fn alert(text: &str) -> String {
+        format!("{}{}", PREFIX, text)
+    }
+
+    #[test]
+    fn regular_alerts() {
+        assert_eq!(parse("hello"), None);
+        assert_eq!(parse(""), None);
+        assert_eq!(parse("servo-gtk\nscript-result\n1\ntoken\n2"), None);
+        assert_eq!(parse(&alert("unknown\n1\n2")), None);
+    }
+
+    #[test]
+    fn load_failures() {
+        let expected = Message::LoadFailed {
+            nonce: "nonce".to_string(),
+            url: "https://example.com/".to_string(),
+            reason: "connection refused\non two lines".to_string(),
+        };
+        assert_eq!(parse(&alert("load-failed\nnonce\nhttps://example.com/\nconnection refused\non two lines")),
+            Some(expected));
+        let expected = Message::LoadFailed {
+            nonce: "nonce".to_string(),
+            url: "https://example.com/".to_string(),
+            reason: String::new(),
+        };
+        assert_eq!(parse(&alert("load-failed\nnonce\nhttps://example.com/")), Some(expected));
+        assert_eq!(parse(&alert("load-failed\nnonce")), None);
+    }
+
+    #[test]
+    fn handler_messages() {
+        let expected = Message::HandlerMessage {
+            token: "token".to_string(),
+            id: 3,
+            name: "name".to_string(),
+            message: serde_json::from_str(r#"{ "text": "a\nb" }"#).unwrap(),
+        };
+        assert_eq!(parse(&alert("message\ntoken\n3\nname\n{\"text\": \"a\\nb\"}")), Some(expected));
+        let expected = Message::HandlerMessage {
+            token: "token".to_string(),
+            id: 3,
+            name: "name".to_string(),
+            message: Value::Null,
+        };
+        assert_eq!(parse(&alert("message\ntoken\n3\nname\nnot JSON")), Some(expected));
+        assert_eq!(parse(&alert("message\ntoken\nnot an id\nname\nnull")), None);
+        assert_eq!(parse(&alert("message\ntoken\n3\nname")), None);
+    }
+
+    #[test]
+    fn script_results() {
+        let expected = Message::ScriptResult {
+            id: 7,
+            token: "token".to_string(),
+            result: serde_json::from_str(r#"[1, "two\nlines"]"#).unwrap(),
+        };
+        assert_eq!(parse(&alert("script-result\n7\ntoken\n[1, \"two\\nlines\"]")), Some(expected));
+        let expected = Message::ScriptError {
+            id: 7,
+            token: "token".to_string(),
+            name: "TypeError".to_string(),
+            message: "x is undefined\nat line 1".to_string(),
+        };
+        assert_eq!(parse(&alert("script-error\n7\ntoken\nTypeError\nx is undefined\nat line 1")), Some(expected));
+        assert_eq!(parse(&alert("script-result\n-1\ntoken\nnull")), None);
+        assert_eq!(parse(&alert("script-result\n7\ntoken")), None);
+    }
+
+    #[test]
+    fn nonces() {
+        let first = nonce();
+        assert_eq!(first.len(), 32);
+        assert!(first.chars().all(|char| char.is_ascii_hexdigit()));
+        assert_ne!(first, nonce());
+    }
+}
@@ -0,0 +1,344 @@
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::ptr;
+use std::rc::Rc;
+use std::sync::atomic::{ATOMIC_BOOL_INIT, AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, Once, ONCE_INIT};
+
+use epoxy;
+use gdk::{GLContext, WindowExt};
+use glib_itc::{Receiver, channel};
+use gtk::{
+    Continue,
+    GLAreaExt,
+    WidgetExt,
+};
+use servo;
+use servo::BrowserId;
+use servo::compositing::windowing::WindowEvent;
+use servo::embedder_traits::{EmbedderMsg, EventLoopWaker};
+use servo::embedder_traits;
+use servo::gl;
+use servo::servo_url::ServoUrl;
+use shared_library::dynamic_library::DynamicLibrary;
+
+use error::Error;
+use eventloop::GtkEventLoopWaker;
+use resources::{self, ResourceReader};
+use settings::Settings;
+use uri_scheme::{UriSchemeRequest, UriSchemeResponse};
+use view::{View, WeakWebView, WebView};
+use window::GtkWindow;
+
+static EPOXY_INIT: Once = ONCE_INIT;
+
+/// Set once a context started Servo, whose options and resources are global to the process.
+static ENGINE_STARTED: AtomicBool = ATOMIC_BOOL_INIT;
+
+thread_local! {
+    static DEFAULT_CONTEXT: WebContext = WebContext::new();
+}
+
+struct State {
+    active_browser: Option<BrowserId>,
+    error_callback: Option<Rc<Fn(&Error)>>,
+    /// The one-time nonces given to the error pages, authenticating their reports to the views.
+    error_page_nonces: Arc<Mutex<VecDeque<String>>>,
+    gl: Rc<gl::Gl>,
+    /// The GL context of all the views, where WebRender is initialized.
+    gl_context: Option<GLContext>,
+    /// The errors of the threads of Servo, reported on the main thread by handle_events().
+    pending_errors: Arc<Mutex<Vec<Error>>>,
+    /// The views given for the new windows Servo is creating, in order.
+    pending_views: VecDeque<WebView>,
+    resources_dir: Option<PathBuf>,
+    rx: Receiver<()>,
+    servo: Option<Rc<RefCell<servo::Servo<GtkWindow>>>>,
+    settings: Settings,
+    shut_down: bool,
+    uri_schemes: HashMap<String, Rc<Fn(&UriSchemeRequest) -> UriSchemeResponse>>,
+    views: HashMap<BrowserId, WeakWebView>,
+    waker: Box<EventLoopWaker>,
+    window: Option<Rc<GtkWindow>>,
+}
+
+/// A single Servo instance shared by all the `WebView`s created with it.
+///
+/// The engine is started when the first view is realized. Only one view is painted at a time: the
+/// one that was last shown or that last received input.
+///
+/// Servo can only run once per process: once a context started it, the views of the other contexts
+/// stay detached, and their `load()` returns `Error::EngineAlreadyStarted` once they are realized.
+#[derive(Clone)]
+pub struct WebContext {
+    state: Rc<RefCell<State>>,
+}
+
+impl WebContext {
+    pub fn new() -> Self {
+        EPOXY_INIT.call_once(|| {
+            epoxy::load_with(|s| {
+                unsafe {
+                    match DynamicLibrary::open(None).unwrap().symbol(s) {
+                        Ok(v) => v,
+                        Err(_) => ptr::null(),
+                    }
+                }
+            });
+        });
+
+        let gl = unsafe {
+            gl::GlFns::load_with(epoxy::get_proc_addr)
+        };
+
+        let (tx, rx) = channel();
+
+        let resources_dir = resources::default_dir();
+
+        let waker = Box::new(GtkEventLoopWaker::new(Arc::new(Mutex::new(tx))));
+
+        WebContext {
+            state: Rc::new(RefCell::new(State {
+                active_browser: None,
+                error_callback: None,
+                error_page_nonces: Arc::new(Mutex::new(VecDeque::new())),
+                gl,
+                gl_context: None,
+                pending_errors: Arc::new(Mutex::new(vec![])),
+                pending_views: VecDeque::new(),
+                resources_dir: resources_dir.clone(),
+                rx,
+                servo: None,
+                settings: Settings::new(resources_dir),
+                shut_down: false,
+                uri_schemes: HashMap::new(),
+                views: HashMap::new(),
+                waker,
+                window: None,
+            })),
+        }
+    }
+
+    /// Check that Servo will find all its resources, see `set_resources_dir()`.
+    pub fn check_resources(&self) -> Result<(), Error> {
+        let state = self.state.borrow();
+        resources::check(state.resources_dir.as_ref().map(PathBuf::as_path))
+    }
+
+    /// Get the GL context to use for `view`: WebRender only knows the one it was initialized in, so
+    /// all the views share the context created for the first one.
+    pub(crate) fn gl_context(&self, view: &View) -> GLContext {
+        let mut state = self.state.borrow_mut();
+        if let Some(ref gl_context) = state.gl_context {
+            return gl_context.clone();
+        }
+        let window = view.get_window().expect("the view is realized");
+        let gl_context = window.create_gl_context().expect("cannot create a GL context");
+        gl_context.realize().expect("cannot realize the GL context");
+        state.gl_context = Some(gl_context.clone());
+        gl_context
+    }
+
+    /// Whether `nonce` was given to an error page and not used yet. It cannot be used again.
+    pub(crate) fn take_error_page_nonce(&self, nonce: &str) -> bool {
+        let state = self.state.borrow();
+        let mut nonces = state.error_page_nonces.lock().unwrap();
+        match nonces.iter().position(|error_page_nonce| error_page_nonce == nonce) {
+            Some(index) => {
+                nonces.remove(index);
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// The callback gets the errors no call can return: a resource file Servo cannot read anymore,
+    /// or a link that cannot be opened in the view given by `WebView::connect_create()`.
+    pub fn connect_error<F: Fn(&Error) + 'static>(&self, callback: F) {
+        self.state.borrow_mut().error_callback = Some(Rc::new(callback));
+    }
+
+    /// Get the context used by `WebView::new()`.
+    pub fn get_default() -> Self {
+        DEFAULT_CONTEXT.with(|context| context.clone())
+    }
+
+    /// Create a browser in the engine for this view, starting the engine if needed.
+    pub(crate) fn attach(&self, webview: &WebView, view: &View) -> Result<BrowserId, Error> {
+        if self.state.borrow().servo.is_none() {
+            self.start(view)?;
+        }
+        let browser_id = BrowserId::new();
+        self.state.borrow_mut().views.insert(browser_id, webview.downgrade());
+        Ok(browser_id)
+    }
+
+    pub(crate) fn detach(&self, browser_id: BrowserId) {
+        let mut state = self.state.borrow_mut();
+        state.views.remove(&browser_id);
+        if state.active_browser == Some(browser_id) {
+            state.active_browser = None;
+        }
+    }
+
+    /// Give the next browser Servo creates for a page to `webview`.
+    pub(crate) fn add_pending_view(&self, webview: &WebView) {
+        self.state.borrow_mut().pending_views.push_back(webview.clone());
+    }
+
+    fn handle_embedder_msg(&self, browser_id: Option<BrowserId>, message: EmbedderMsg) {
+        match message {
+            EmbedderMsg::BrowserCreated(new_browser_id) => {
+                let webview = self.state.borrow_mut().pending_views.pop_front();
+                if let Some(webview) = webview {
+                    self.state.borrow_mut().views.insert(new_browser_id, webview.downgrade());
+                    webview.adopt_browser(new_browser_id, self);
+                }
+                return;
+            },
+            EmbedderMsg::Shutdown => {
+                self.state.borrow_mut().shut_down = true;
+                return;
+            },
+            _ => (),
+        }
+        let webview = browser_id
+            .and_then(|browser_id| self.state.borrow().views.get(&browser_id).and_then(WeakWebView::upgrade));
+        if let Some(webview) = webview {
+            webview.handle_embedder_msg(message);
+        }
+    }
+
+    /// Send the events to Servo and dispatch the embedder messages it produced to the views.
+    ///
+    /// No borrow is held while the messages are dispatched, so the callbacks are free to call back
+    /// into the `WebView`s.
+    pub(crate) fn handle_events(&self, events: Vec<WindowEvent>) {
+        let servo = self.state.borrow().servo.clone();
+        if let Some(servo) = servo {
+            let messages = {
+                let mut servo = servo.borrow_mut();
+                servo.handle_events(events);
+                servo.get_events()
+            };
+            for (browser_id, message) in messages {
+                self.handle_embedder_msg(browser_id, message);
+            }
+        }
+        let errors: Vec<_> = self.state.borrow().pending_errors.lock().unwrap().drain(..).collect();
+        for error in errors {
+            self.report_error(error);
+        }
+    }
+
+    /// Get the settings of the engine, which are global to the process once it is started.
+    pub fn get_settings(&self) -> Settings {
+        self.state.borrow().settings.clone()
+    }
+
+    pub(crate) fn is_active(&self, browser_id: BrowserId) -> bool {
+        self.state.borrow().active_browser == Some(browser_id)
+    }
+
+    pub fn is_shut_down(&self) -> bool {
+        self.state.borrow().shut_down
+    }
+
+    pub(crate) fn report_error(&self, error: Error) {
+        let callback = self.state.borrow().error_callback.clone();
+        if let Some(callback) = callback {
+            callback(&error);
+        }
+    }
+
+    /// Let `handler` answer the navigations to the URLs of `scheme`, e.g. `app` for `app://index.html`.
+    ///
+    /// Servo's network stack cannot be extended, so only the documents loaded in the views go
+    /// through the handler, and they are loaded as `data:` URLs: the resources they refer to with
+    /// that scheme or with relative URLs fail to load, and must be inlined or use absolute URLs of
+    /// another scheme. The documents also get an opaque origin.
+    pub fn register_uri_scheme<F>(&self, scheme: &str, handler: F)
+        where F: Fn(&UriSchemeRequest) -> UriSchemeResponse + 'static,
+    {
+        self.state.borrow_mut().uri_schemes.insert(scheme.to_lowercase(), Rc::new(handler));
+    }
+
+    /// Get the URL to load instead of `url`: its response as a `data:` URL if a handler is
+    /// registered for its scheme.
+    pub(crate) fn resolve_uri_scheme(&self, url: &ServoUrl) -> Option<ServoUrl> {
+        let handler = self.state.borrow().uri_schemes.get(url.scheme()).cloned()?;
+        let response = handler(&UriSchemeRequest::new(url.clone()));
+        ServoUrl::parse(&response.to_data_url()).ok()
+    }
+
+    /// Make `view` the one Servo paints into and shows the browser `browser_id`.
+    pub(crate) fn select(&self, browser_id: BrowserId, view: &View) {
+        {
+            let mut state = self.state.borrow_mut();
+            state.active_browser = Some(browser_id);
+            if let Some(ref window) = state.window {
+                window.set_view(view.clone());
+            }
+        }
+        self.handle_events(vec![WindowEvent::SelectBrowser(browser_id), WindowEvent::Resize]);
+    }
+
+    /// Set the directory containing `prefs.json`, `user-agent.css` and the other resources Servo
+    /// needs.
+    ///
+    /// By default, the directory is taken from the `SERVO_GTK_RESOURCES` environment variable, or
+    /// is the `resources` directory of the current directory. With the `embedded-resources`
+    /// feature, the files missing from the directory are taken from copies compiled into the crate.
+    /// This must be called before the first view is realized: Servo reads its resources from the
+    /// directory it was started with.
+    pub fn set_resources_dir<P: AsRef<Path>>(&self, dir: P) -> Result<(), Error> {
+        if ENGINE_STARTED.load(Ordering::SeqCst) {
+            return Err(Error::EngineAlreadyStarted);
+        }
+        let dir = dir.as_ref();
+        resources::check(Some(dir))?;
+        let mut state = self.state.borrow_mut();
+        state.settings.set_resources_dir(dir.to_path_buf());
+        state.resources_dir = Some(dir.to_path_buf());
+        Ok(())
+    }
+
+    fn start(&self, view: &View) -> Result<(), Error> {
+        // Servo cannot report a missing resource: it would crash on the empty file it gets instead.
+        self.check_resources()?;
+        if ENGINE_STARTED.swap(true, Ordering::SeqCst) {
+            return Err(Error::EngineAlreadyStarted);
+        }
+
+        // Webrender must be initialized with the GL context current.
+        view.make_current();
+
+        let (resources_dir, settings, nonces, errors) = {
+            let state = self.state.borrow();
+            let nonces = state.error_page_nonces.clone();
+            (state.resources_dir.clone(), state.settings.clone(), nonces, state.pending_errors.clone())
+        };
+        let reader = ResourceReader::new(resources_dir, settings.clone(), nonces, errors);
+        embedder_traits::resources::set(Box::new(reader));
+        settings.apply_startup_options();
+        settings.set_live();
+
+        let window = {
+            let state = self.state.borrow();
+            Rc::new(GtkWindow::new(state.gl.clone(), view.clone(), state.waker.clone()))
+        };
+        let servo = Rc::new(RefCell::new(servo::Servo::new(window.clone())));
+
+        let mut state = self.state.borrow_mut();
+        state.window = Some(window);
+        state.servo = Some(servo);
+
+        let context = self.clone();
+        state.rx.connect_recv(move |()| {
+            context.handle_events(vec![]);
+            Continue(true)
+        });
+        Ok(())
+    }
+}
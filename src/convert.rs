@@ -2,8 +2,399 @@ use gdk::keyval_to_unicode;
 use gdk::ModifierType;
 use gdk::enums::key as gdk_key;
 use gdk_sys::{GDK_BUTTON_MIDDLE, GDK_BUTTON_PRIMARY, GDK_BUTTON_SECONDARY};
-use keyboard_types::{Key, Modifiers};
+use keyboard_types::{Code, Key, Location, Modifiers};
+use servo::net_traits::net_error_list::NetError;
 use servo::script_traits::MouseButton;
+use servo::style_traits::cursor::CursorKind;
+
+/// Keys that do not produce a character: GDK keyval, key, physical key on a US keyboard and location.
+static NAMED_KEYS: [(gdk_key::Key, Key, Code, Location); 166] = [
+    (gdk_key::BackSpace, Key::Backspace, Code::Backspace, Location::Standard),
+    (gdk_key::Tab, Key::Tab, Code::Tab, Location::Standard),
+    (gdk_key::ISO_Left_Tab, Key::Tab, Code::Tab, Location::Standard),
+    (gdk_key::Return, Key::Enter, Code::Enter, Location::Standard),
+    (gdk_key::Escape, Key::Escape, Code::Escape, Location::Standard),
+    (gdk_key::Insert, Key::Insert, Code::Insert, Location::Standard),
+    (gdk_key::Delete, Key::Delete, Code::Delete, Location::Standard),
+    (gdk_key::Clear, Key::Clear, Code::NumpadClear, Location::Standard),
+    (gdk_key::Left, Key::ArrowLeft, Code::ArrowLeft, Location::Standard),
+    (gdk_key::Up, Key::ArrowUp, Code::ArrowUp, Location::Standard),
+    (gdk_key::Right, Key::ArrowRight, Code::ArrowRight, Location::Standard),
+    (gdk_key::Down, Key::ArrowDown, Code::ArrowDown, Location::Standard),
+    (gdk_key::Page_Up, Key::PageUp, Code::PageUp, Location::Standard),
+    (gdk_key::Page_Down, Key::PageDown, Code::PageDown, Location::Standard),
+    (gdk_key::Home, Key::Home, Code::Home, Location::Standard),
+    (gdk_key::End, Key::End, Code::End, Location::Standard),
+    (gdk_key::Caps_Lock, Key::CapsLock, Code::CapsLock, Location::Standard),
+    (gdk_key::Scroll_Lock, Key::ScrollLock, Code::ScrollLock, Location::Standard),
+    (gdk_key::Num_Lock, Key::NumLock, Code::NumLock, Location::Numpad),
+    (gdk_key::Print, Key::PrintScreen, Code::PrintScreen, Location::Standard),
+    (gdk_key::Sys_Req, Key::PrintScreen, Code::PrintScreen, Location::Standard),
+    (gdk_key::Pause, Key::Pause, Code::Pause, Location::Standard),
+    (gdk_key::Break, Key::Pause, Code::Pause, Location::Standard),
+    (gdk_key::Menu, Key::ContextMenu, Code::ContextMenu, Location::Standard),
+    (gdk_key::Shift_L, Key::Shift, Code::ShiftLeft, Location::Left),
+    (gdk_key::Shift_R, Key::Shift, Code::ShiftRight, Location::Right),
+    (gdk_key::Control_L, Key::Control, Code::ControlLeft, Location::Left),
+    (gdk_key::Control_R, Key::Control, Code::ControlRight, Location::Right),
+    (gdk_key::Alt_L, Key::Alt, Code::AltLeft, Location::Left),
+    (gdk_key::Alt_R, Key::Alt, Code::AltRight, Location::Right),
+    (gdk_key::ISO_Level3_Shift, Key::AltGraph, Code::AltRight, Location::Right),
+    (gdk_key::Meta_L, Key::Meta, Code::MetaLeft, Location::Left),
+    (gdk_key::Meta_R, Key::Meta, Code::MetaRight, Location::Right),
+    (gdk_key::Super_L, Key::Meta, Code::MetaLeft, Location::Left),
+    (gdk_key::Super_R, Key::Meta, Code::MetaRight, Location::Right),
+    (gdk_key::Hyper_L, Key::Hyper, Code::Hyper, Location::Left),
+    (gdk_key::Hyper_R, Key::Hyper, Code::Hyper, Location::Right),
+    (gdk_key::F1, Key::F1, Code::F1, Location::Standard),
+    (gdk_key::F2, Key::F2, Code::F2, Location::Standard),
+    (gdk_key::F3, Key::F3, Code::F3, Location::Standard),
+    (gdk_key::F4, Key::F4, Code::F4, Location::Standard),
+    (gdk_key::F5, Key::F5, Code::F5, Location::Standard),
+    (gdk_key::F6, Key::F6, Code::F6, Location::Standard),
+    (gdk_key::F7, Key::F7, Code::F7, Location::Standard),
+    (gdk_key::F8, Key::F8, Code::F8, Location::Standard),
+    (gdk_key::F9, Key::F9, Code::F9, Location::Standard),
+    (gdk_key::F10, Key::F10, Code::F10, Location::Standard),
+    (gdk_key::F11, Key::F11, Code::F11, Location::Standard),
+    (gdk_key::F12, Key::F12, Code::F12, Location::Standard),
+    // keyboard-types has neither a key nor a code for F13 to F24.
+    (gdk_key::F13, Key::Unidentified, Code::Unidentified, Location::Standard),
+    (gdk_key::F14, Key::Unidentified, Code::Unidentified, Location::Standard),
+    (gdk_key::F15, Key::Unidentified, Code::Unidentified, Location::Standard),
+    (gdk_key::F16, Key::Unidentified, Code::Unidentified, Location::Standard),
+    (gdk_key::F17, Key::Unidentified, Code::Unidentified, Location::Standard),
+    (gdk_key::F18, Key::Unidentified, Code::Unidentified, Location::Standard),
+    (gdk_key::F19, Key::Unidentified, Code::Unidentified, Location::Standard),
+    (gdk_key::F20, Key::Unidentified, Code::Unidentified, Location::Standard),
+    (gdk_key::F21, Key::Unidentified, Code::Unidentified, Location::Standard),
+    (gdk_key::F22, Key::Unidentified, Code::Unidentified, Location::Standard),
+    (gdk_key::F23, Key::Unidentified, Code::Unidentified, Location::Standard),
+    (gdk_key::F24, Key::Unidentified, Code::Unidentified, Location::Standard),
+    (gdk_key::KP_Enter, Key::Enter, Code::NumpadEnter, Location::Numpad),
+    (gdk_key::KP_Home, Key::Home, Code::Numpad7, Location::Numpad),
+    (gdk_key::KP_Up, Key::ArrowUp, Code::Numpad8, Location::Numpad),
+    (gdk_key::KP_Page_Up, Key::PageUp, Code::Numpad9, Location::Numpad),
+    (gdk_key::KP_Left, Key::ArrowLeft, Code::Numpad4, Location::Numpad),
+    (gdk_key::KP_Begin, Key::Clear, Code::Numpad5, Location::Numpad),
+    (gdk_key::KP_Right, Key::ArrowRight, Code::Numpad6, Location::Numpad),
+    (gdk_key::KP_End, Key::End, Code::Numpad1, Location::Numpad),
+    (gdk_key::KP_Down, Key::ArrowDown, Code::Numpad2, Location::Numpad),
+    (gdk_key::KP_Page_Down, Key::PageDown, Code::Numpad3, Location::Numpad),
+    (gdk_key::KP_Insert, Key::Insert, Code::Numpad0, Location::Numpad),
+    (gdk_key::KP_Delete, Key::Delete, Code::NumpadDecimal, Location::Numpad),
+    (gdk_key::Undo, Key::Undo, Code::Undo, Location::Standard),
+    (gdk_key::Redo, Key::Redo, Code::Unidentified, Location::Standard),
+    (gdk_key::Find, Key::Find, Code::Find, Location::Standard),
+    (gdk_key::Help, Key::Help, Code::Help, Location::Standard),
+    (gdk_key::Cancel, Key::Cancel, Code::Abort, Location::Standard),
+    (gdk_key::Execute, Key::Execute, Code::Unidentified, Location::Standard),
+    (gdk_key::Select, Key::Select, Code::Select, Location::Standard),
+    (gdk_key::Copy, Key::Copy, Code::Copy, Location::Standard),
+    (gdk_key::Cut, Key::Cut, Code::Cut, Location::Standard),
+    (gdk_key::Paste, Key::Paste, Code::Paste, Location::Standard),
+    (gdk_key::Open, Key::Open, Code::Open, Location::Standard),
+    (gdk_key::Close, Key::Close, Code::Unidentified, Location::Standard),
+    (gdk_key::Save, Key::Save, Code::Unidentified, Location::Standard),
+    (gdk_key::New, Key::New, Code::Unidentified, Location::Standard),
+    (gdk_key::ZoomIn, Key::ZoomIn, Code::Unidentified, Location::Standard),
+    (gdk_key::ZoomOut, Key::ZoomOut, Code::Unidentified, Location::Standard),
+    (gdk_key::AudioPlay, Key::MediaPlayPause, Code::MediaPlayPause, Location::Standard),
+    (gdk_key::AudioPause, Key::MediaPause, Code::MediaPlayPause, Location::Standard),
+    (gdk_key::AudioStop, Key::MediaStop, Code::MediaStop, Location::Standard),
+    (gdk_key::AudioPrev, Key::MediaTrackPrevious, Code::MediaTrackPrevious, Location::Standard),
+    (gdk_key::AudioNext, Key::MediaTrackNext, Code::MediaTrackNext, Location::Standard),
+    (gdk_key::AudioRecord, Key::MediaRecord, Code::Unidentified, Location::Standard),
+    (gdk_key::AudioRewind, Key::MediaRewind, Code::Unidentified, Location::Standard),
+    (gdk_key::AudioForward, Key::MediaFastForward, Code::Unidentified, Location::Standard),
+    (gdk_key::AudioMute, Key::AudioVolumeMute, Code::AudioVolumeMute, Location::Standard),
+    (gdk_key::AudioLowerVolume, Key::AudioVolumeDown, Code::AudioVolumeDown, Location::Standard),
+    (gdk_key::AudioRaiseVolume, Key::AudioVolumeUp, Code::AudioVolumeUp, Location::Standard),
+    (gdk_key::AudioMicMute, Key::MicrophoneVolumeMute, Code::Unidentified, Location::Standard),
+    (gdk_key::AudioMedia, Key::LaunchMediaPlayer, Code::MediaSelect, Location::Standard),
+    (gdk_key::Eject, Key::Eject, Code::Eject, Location::Standard),
+    (gdk_key::Back, Key::BrowserBack, Code::BrowserBack, Location::Standard),
+    (gdk_key::Forward, Key::BrowserForward, Code::BrowserForward, Location::Standard),
+    (gdk_key::Refresh, Key::BrowserRefresh, Code::BrowserRefresh, Location::Standard),
+    (gdk_key::Stop, Key::BrowserStop, Code::BrowserStop, Location::Standard),
+    (gdk_key::Search, Key::BrowserSearch, Code::BrowserSearch, Location::Standard),
+    (gdk_key::HomePage, Key::BrowserHome, Code::BrowserHome, Location::Standard),
+    (gdk_key::Favorites, Key::BrowserFavorites, Code::BrowserFavorites, Location::Standard),
+    (gdk_key::Mail, Key::LaunchMail, Code::LaunchMail, Location::Standard),
+    (gdk_key::Calculator, Key::LaunchApplication2, Code::LaunchApp2, Location::Standard),
+    (gdk_key::MyComputer, Key::LaunchApplication1, Code::LaunchApp1, Location::Standard),
+    (gdk_key::Explorer, Key::LaunchApplication1, Code::LaunchApp1, Location::Standard),
+    (gdk_key::Calendar, Key::LaunchCalendar, Code::Unidentified, Location::Standard),
+    (gdk_key::WWW, Key::LaunchWebBrowser, Code::Unidentified, Location::Standard),
+    (gdk_key::Music, Key::LaunchMusicPlayer, Code::Unidentified, Location::Standard),
+    (gdk_key::ScreenSaver, Key::LaunchScreenSaver, Code::Unidentified, Location::Standard),
+    (gdk_key::WebCam, Key::LaunchWebCam, Code::Unidentified, Location::Standard),
+    (gdk_key::Phone, Key::LaunchPhone, Code::Unidentified, Location::Standard),
+    (gdk_key::Word, Key::LaunchWordProcessor, Code::Unidentified, Location::Standard),
+    (gdk_key::Excel, Key::LaunchSpreadsheet, Code::Unidentified, Location::Standard),
+    (gdk_key::Reply, Key::MailReply, Code::Unidentified, Location::Standard),
+    (gdk_key::MailForward, Key::MailForward, Code::Unidentified, Location::Standard),
+    (gdk_key::Send, Key::MailSend, Code::Unidentified, Location::Standard),
+    (gdk_key::Spell, Key::SpellCheck, Code::Unidentified, Location::Standard),
+    (gdk_key::PowerOff, Key::Power, Code::Power, Location::Standard),
+    (gdk_key::PowerDown, Key::PowerOff, Code::Power, Location::Standard),
+    (gdk_key::Sleep, Key::Standby, Code::Sleep, Location::Standard),
+    (gdk_key::Standby, Key::Standby, Code::Sleep, Location::Standard),
+    (gdk_key::Suspend, Key::Standby, Code::Suspend, Location::Standard),
+    (gdk_key::Hibernate, Key::Hibernate, Code::Unidentified, Location::Standard),
+    (gdk_key::WakeUp, Key::WakeUp, Code::WakeUp, Location::Standard),
+    (gdk_key::MonBrightnessUp, Key::BrightnessUp, Code::Unidentified, Location::Standard),
+    (gdk_key::MonBrightnessDown, Key::BrightnessDown, Code::Unidentified, Location::Standard),
+    (gdk_key::Multi_key, Key::Compose, Code::Unidentified, Location::Standard),
+    (gdk_key::Henkan, Key::Convert, Code::Convert, Location::Standard),
+    (gdk_key::Muhenkan, Key::NonConvert, Code::NonConvert, Location::Standard),
+    (gdk_key::Kanji, Key::KanjiMode, Code::Unidentified, Location::Standard),
+    (gdk_key::Hiragana, Key::Hiragana, Code::Hiragana, Location::Standard),
+    (gdk_key::Katakana, Key::Katakana, Code::Katakana, Location::Standard),
+    (gdk_key::Hiragana_Katakana, Key::HiraganaKatakana, Code::KanaMode, Location::Standard),
+    (gdk_key::Zenkaku_Hankaku, Key::ZenkakuHankaku, Code::Backquote, Location::Standard),
+    (gdk_key::Eisu_toggle, Key::Eisu, Code::Unidentified, Location::Standard),
+    (gdk_key::Hangul, Key::HangulMode, Code::Lang1, Location::Standard),
+    (gdk_key::Hangul_Hanja, Key::HanjaMode, Code::Lang2, Location::Standard),
+    (gdk_key::dead_grave, Key::Dead, Code::Unidentified, Location::Standard),
+    (gdk_key::dead_acute, Key::Dead, Code::Unidentified, Location::Standard),
+    (gdk_key::dead_circumflex, Key::Dead, Code::Unidentified, Location::Standard),
+    (gdk_key::dead_tilde, Key::Dead, Code::Unidentified, Location::Standard),
+    (gdk_key::dead_macron, Key::Dead, Code::Unidentified, Location::Standard),
+    (gdk_key::dead_breve, Key::Dead, Code::Unidentified, Location::Standard),
+    (gdk_key::dead_abovedot, Key::Dead, Code::Unidentified, Location::Standard),
+    (gdk_key::dead_diaeresis, Key::Dead, Code::Unidentified, Location::Standard),
+    (gdk_key::dead_abovering, Key::Dead, Code::Unidentified, Location::Standard),
+    (gdk_key::dead_doubleacute, Key::Dead, Code::Unidentified, Location::Standard),
+    (gdk_key::dead_caron, Key::Dead, Code::Unidentified, Location::Standard),
+    (gdk_key::dead_cedilla, Key::Dead, Code::Unidentified, Location::Standard),
+    (gdk_key::dead_ogonek, Key::Dead, Code::Unidentified, Location::Standard),
+    (gdk_key::dead_iota, Key::Dead, Code::Unidentified, Location::Standard),
+    (gdk_key::dead_voiced_sound, Key::Dead, Code::Unidentified, Location::Standard),
+    (gdk_key::dead_semivoiced_sound, Key::Dead, Code::Unidentified, Location::Standard),
+    (gdk_key::dead_belowdot, Key::Dead, Code::Unidentified, Location::Standard),
+    (gdk_key::dead_hook, Key::Dead, Code::Unidentified, Location::Standard),
+    (gdk_key::dead_horn, Key::Dead, Code::Unidentified, Location::Standard),
+    (gdk_key::dead_stroke, Key::Dead, Code::Unidentified, Location::Standard),
+];
+
+/// Keypad keys producing a character.
+static KEYPAD_KEYS: [(gdk_key::Key, char, Code); 18] = [
+    (gdk_key::KP_0, '0', Code::Numpad0),
+    (gdk_key::KP_1, '1', Code::Numpad1),
+    (gdk_key::KP_2, '2', Code::Numpad2),
+    (gdk_key::KP_3, '3', Code::Numpad3),
+    (gdk_key::KP_4, '4', Code::Numpad4),
+    (gdk_key::KP_5, '5', Code::Numpad5),
+    (gdk_key::KP_6, '6', Code::Numpad6),
+    (gdk_key::KP_7, '7', Code::Numpad7),
+    (gdk_key::KP_8, '8', Code::Numpad8),
+    (gdk_key::KP_9, '9', Code::Numpad9),
+    (gdk_key::KP_Decimal, '.', Code::NumpadDecimal),
+    (gdk_key::KP_Separator, ',', Code::NumpadComma),
+    (gdk_key::KP_Divide, '/', Code::NumpadDivide),
+    (gdk_key::KP_Multiply, '*', Code::NumpadMultiply),
+    (gdk_key::KP_Subtract, '-', Code::NumpadSubtract),
+    (gdk_key::KP_Add, '+', Code::NumpadAdd),
+    (gdk_key::KP_Equal, '=', Code::NumpadEqual),
+    (gdk_key::KP_Space, ' ', Code::Space),
+];
+
+/// Linux evdev scan codes and the physical key they are.
+#[cfg(target_os = "linux")]
+static EVDEV_CODES: [(u16, Code); 150] = [
+    (1, Code::Escape),
+    (2, Code::Digit1),
+    (3, Code::Digit2),
+    (4, Code::Digit3),
+    (5, Code::Digit4),
+    (6, Code::Digit5),
+    (7, Code::Digit6),
+    (8, Code::Digit7),
+    (9, Code::Digit8),
+    (10, Code::Digit9),
+    (11, Code::Digit0),
+    (12, Code::Minus),
+    (13, Code::Equal),
+    (14, Code::Backspace),
+    (15, Code::Tab),
+    (16, Code::KeyQ),
+    (17, Code::KeyW),
+    (18, Code::KeyE),
+    (19, Code::KeyR),
+    (20, Code::KeyT),
+    (21, Code::KeyY),
+    (22, Code::KeyU),
+    (23, Code::KeyI),
+    (24, Code::KeyO),
+    (25, Code::KeyP),
+    (26, Code::BracketLeft),
+    (27, Code::BracketRight),
+    (28, Code::Enter),
+    (29, Code::ControlLeft),
+    (30, Code::KeyA),
+    (31, Code::KeyS),
+    (32, Code::KeyD),
+    (33, Code::KeyF),
+    (34, Code::KeyG),
+    (35, Code::KeyH),
+    (36, Code::KeyJ),
+    (37, Code::KeyK),
+    (38, Code::KeyL),
+    (39, Code::Semicolon),
+    (40, Code::Quote),
+    (41, Code::Backquote),
+    (42, Code::ShiftLeft),
+    (43, Code::Backslash),
+    (44, Code::KeyZ),
+    (45, Code::KeyX),
+    (46, Code::KeyC),
+    (47, Code::KeyV),
+    (48, Code::KeyB),
+    (49, Code::KeyN),
+    (50, Code::KeyM),
+    (51, Code::Comma),
+    (52, Code::Period),
+    (53, Code::Slash),
+    (54, Code::ShiftRight),
+    (55, Code::NumpadMultiply),
+    (56, Code::AltLeft),
+    (57, Code::Space),
+    (58, Code::CapsLock),
+    (59, Code::F1),
+    (60, Code::F2),
+    (61, Code::F3),
+    (62, Code::F4),
+    (63, Code::F5),
+    (64, Code::F6),
+    (65, Code::F7),
+    (66, Code::F8),
+    (67, Code::F9),
+    (68, Code::F10),
+    (69, Code::NumLock),
+    (70, Code::ScrollLock),
+    (71, Code::Numpad7),
+    (72, Code::Numpad8),
+    (73, Code::Numpad9),
+    (74, Code::NumpadSubtract),
+    (75, Code::Numpad4),
+    (76, Code::Numpad5),
+    (77, Code::Numpad6),
+    (78, Code::NumpadAdd),
+    (79, Code::Numpad1),
+    (80, Code::Numpad2),
+    (81, Code::Numpad3),
+    (82, Code::Numpad0),
+    (83, Code::NumpadDecimal),
+    (86, Code::IntlBackslash),
+    (87, Code::F11),
+    (88, Code::F12),
+    (89, Code::IntlRo),
+    (90, Code::Katakana),
+    (91, Code::Hiragana),
+    (92, Code::Convert),
+    (93, Code::KanaMode),
+    (94, Code::NonConvert),
+    (96, Code::NumpadEnter),
+    (97, Code::ControlRight),
+    (98, Code::NumpadDivide),
+    (99, Code::PrintScreen),
+    (100, Code::AltRight),
+    (102, Code::Home),
+    (103, Code::ArrowUp),
+    (104, Code::PageUp),
+    (105, Code::ArrowLeft),
+    (106, Code::ArrowRight),
+    (107, Code::End),
+    (108, Code::ArrowDown),
+    (109, Code::PageDown),
+    (110, Code::Insert),
+    (111, Code::Delete),
+    (113, Code::AudioVolumeMute),
+    (114, Code::AudioVolumeDown),
+    (115, Code::AudioVolumeUp),
+    (116, Code::Power),
+    (117, Code::NumpadEqual),
+    (119, Code::Pause),
+    (121, Code::NumpadComma),
+    (122, Code::Lang1),
+    (123, Code::Lang2),
+    (124, Code::IntlYen),
+    (125, Code::MetaLeft),
+    (126, Code::MetaRight),
+    (127, Code::ContextMenu),
+    (128, Code::BrowserStop),
+    (129, Code::Again),
+    (130, Code::Props),
+    (131, Code::Undo),
+    (132, Code::Select),
+    (133, Code::Copy),
+    (134, Code::Open),
+    (135, Code::Paste),
+    (136, Code::Find),
+    (137, Code::Cut),
+    (138, Code::Help),
+    (140, Code::LaunchApp2),
+    (142, Code::Sleep),
+    (143, Code::WakeUp),
+    (155, Code::LaunchMail),
+    (156, Code::BrowserFavorites),
+    (157, Code::LaunchApp1),
+    (158, Code::BrowserBack),
+    (159, Code::BrowserForward),
+    (161, Code::Eject),
+    (163, Code::MediaTrackNext),
+    (164, Code::MediaPlayPause),
+    (165, Code::MediaTrackPrevious),
+    (166, Code::MediaStop),
+    (172, Code::BrowserHome),
+    (173, Code::BrowserRefresh),
+    (179, Code::NumpadParenLeft),
+    (180, Code::NumpadParenRight),
+    (217, Code::BrowserSearch),
+    (226, Code::MediaSelect),
+];
+
+/// Get the name of the GDK cursor for a CSS cursor.
+pub fn cursor_name(cursor: CursorKind) -> &'static str {
+    match cursor {
+        CursorKind::None => "none",
+        CursorKind::Default => "default",
+        CursorKind::Pointer => "pointer",
+        CursorKind::ContextMenu => "context-menu",
+        CursorKind::Help => "help",
+        CursorKind::Progress => "progress",
+        CursorKind::Wait => "wait",
+        CursorKind::Cell => "cell",
+        CursorKind::Crosshair => "crosshair",
+        CursorKind::Text => "text",
+        CursorKind::VerticalText => "vertical-text",
+        CursorKind::Alias => "alias",
+        CursorKind::Copy => "copy",
+        CursorKind::Move => "move",
+        CursorKind::NoDrop => "no-drop",
+        CursorKind::NotAllowed => "not-allowed",
+        CursorKind::Grab => "grab",
+        CursorKind::Grabbing => "grabbing",
+        CursorKind::EResize => "e-resize",
+        CursorKind::NResize => "n-resize",
+        CursorKind::NeResize => "ne-resize",
+        CursorKind::NwResize => "nw-resize",
+        CursorKind::SResize => "s-resize",
+        CursorKind::SeResize => "se-resize",
+        CursorKind::SwResize => "sw-resize",
+        CursorKind::WResize => "w-resize",
+        CursorKind::EwResize => "ew-resize",
+        CursorKind::NsResize => "ns-resize",
+        CursorKind::NeswResize => "nesw-resize",
+        CursorKind::NwseResize => "nwse-resize",
+        CursorKind::ColResize => "col-resize",
+        CursorKind::RowResize => "row-resize",
+        CursorKind::AllScroll => "all-scroll",
+        CursorKind::ZoomIn => "zoom-in",
+        CursorKind::ZoomOut => "zoom-out",
+    }
+}
 
 pub fn modifiers(modifiers: ModifierType) -> Modifiers {
     let mut result = Modifiers::empty();
@@ -22,157 +413,277 @@ pub fn modifiers(modifiers: ModifierType) -> Modifiers {
     result
 }
 
-pub fn mouse_button(gtk_button: u32) -> MouseButton {
+/// Guess the error from the English reason Servo shows on its error pages. This is a best effort:
+/// the reasons not recognized give `NetError::FAILED`.
+pub fn net_error(reason: &str) -> NetError {
+    let reason = reason.to_lowercase();
+    if reason.contains("certificate") {
+        NetError::CERT_INVALID
+    }
+    else if reason.contains("refused") {
+        NetError::CONNECTION_REFUSED
+    }
+    else if reason.contains("reset") {
+        NetError::CONNECTION_RESET
+    }
+    else if reason.contains("timed out") {
+        NetError::TIMED_OUT
+    }
+    else if reason.contains("lookup") || reason.contains("resolve") {
+        NetError::NAME_NOT_RESOLVED
+    }
+    else if reason.contains("unreachable") {
+        NetError::ADDRESS_UNREACHABLE
+    }
+    else {
+        NetError::FAILED
+    }
+}
+
+/// Servo only knows about the three main buttons, so the others are `None`.
+pub fn mouse_button(gtk_button: u32) -> Option<MouseButton> {
     match gtk_button as i32 {
-        GDK_BUTTON_MIDDLE => MouseButton::Middle,
-        GDK_BUTTON_PRIMARY => MouseButton::Left,
-        GDK_BUTTON_SECONDARY => MouseButton::Right,
-        _ => panic!("unexpected value for gtk_button"),
+        GDK_BUTTON_MIDDLE => Some(MouseButton::Middle),
+        GDK_BUTTON_PRIMARY => Some(MouseButton::Left),
+        GDK_BUTTON_SECONDARY => Some(MouseButton::Right),
+        _ => None,
+    }
+}
+
+/// Percent-encode everything but the characters that are never special in a URL.
+pub fn percent_encode(bytes: &[u8]) -> String {
+    let mut result = String::with_capacity(bytes.len());
+    for &byte in bytes {
+        match byte {
+            b'a' ..= b'z' | b'A' ..= b'Z' | b'0' ..= b'9' | b'-' | b'_' | b'.' | b'(' | b')' | b'*' | b'!' =>
+                result.push(byte as char),
+            _ => result.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    result
+}
+
+/// Convert a hardware keycode to the physical key, when the keycodes are known for the platform.
+#[cfg(target_os = "linux")]
+pub fn code(hardware_keycode: u16) -> Option<Code> {
+    // X11 and Wayland keycodes are the evdev scan codes shifted by 8.
+    let scan_code = hardware_keycode.checked_sub(8)?;
+    EVDEV_CODES.iter()
+        .find(|&&(known, _)| known == scan_code)
+        .map(|&(_, code)| code)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn code(_hardware_keycode: u16) -> Option<Code> {
+    None
+}
+
+/// Convert a GDK keyval to the key value, the physical key it is on for a US layout and its
+/// location.
+pub fn key(keyval: gdk_key::Key) -> (Key, Code, Location) {
+    if let Some(&(_, ref key, code, location)) = NAMED_KEYS.iter().find(|&&(known, ..)| known == keyval) {
+        return (key.clone(), code, location);
+    }
+    if let Some(&(_, char, code)) = KEYPAD_KEYS.iter().find(|&&(known, ..)| known == keyval) {
+        return (Key::Character(char.to_string()), code, Location::Numpad);
+    }
+    match keyval_to_unicode(keyval) {
+        Some(char) if !char.is_control() => (Key::Character(char.to_string()), char_code(char), Location::Standard),
+        _ => (Key::Unidentified, Code::Unidentified, Location::Standard),
     }
 }
 
-pub fn key(gdk_key: gdk_key::Key) -> (Option<char>, Option<Key>) {
-    let unicode =
-        keyval_to_unicode(gdk_key)
-            .and_then(|char|
-                      if char.is_control() {
-                          None
-                      }
-                      else {
-                          Some(char)
-                      }
-            );
-    let key =
-        match gdk_key {
-            //gdk_key::space => Key::Space,
-            //gdk_key::apostrophe => Key::Apostrophe,
-            //gdk_key::comma => Key::Comma,
-            //gdk_key::minus => Key::Minus,
-            //gdk_key::period => Key::Period,
-            //gdk_key::slash => Key::Slash,
-            //gdk_key::_0 => Key::Num0,
-            //gdk_key::_1 => Key::Num1,
-            //gdk_key::_2 => Key::Num2,
-            //gdk_key::_3 => Key::Num3,
-            //gdk_key::_4 => Key::Num4,
-            //gdk_key::_5 => Key::Num5,
-            //gdk_key::_6 => Key::Num6,
-            //gdk_key::_7 => Key::Num7,
-            //gdk_key::_8 => Key::Num8,
-            //gdk_key::_9 => Key::Num9,
-            //gdk_key::semicolon => Key::Semicolon,
-            //gdk_key::equal => Key::Equal,
-            //gdk_key::A | gdk_key::a => Key::A,
-            //gdk_key::B | gdk_key::b => Key::B,
-            //gdk_key::C | gdk_key::c => Key::C,
-            //gdk_key::D | gdk_key::d => Key::D,
-            //gdk_key::E | gdk_key::e => Key::E,
-            //gdk_key::F | gdk_key::f => Key::F,
-            //gdk_key::G | gdk_key::g => Key::G,
-            //gdk_key::H | gdk_key::h => Key::H,
-            //gdk_key::I | gdk_key::i => Key::I,
-            //gdk_key::J | gdk_key::j => Key::J,
-            //gdk_key::K | gdk_key::k => Key::K,
-            //gdk_key::L | gdk_key::l => Key::L,
-            //gdk_key::M | gdk_key::m => Key::M,
-            //gdk_key::N | gdk_key::n => Key::N,
-            //gdk_key::O | gdk_key::o => Key::O,
-            //gdk_key::P | gdk_key::p => Key::P,
-            //gdk_key::Q | gdk_key::q => Key::Q,
-            //gdk_key::R | gdk_key::r => Key::R,
-            //gdk_key::S | gdk_key::s => Key::S,
-            //gdk_key::T | gdk_key::t => Key::T,
-            //gdk_key::U | gdk_key::u => Key::U,
-            //gdk_key::V | gdk_key::v => Key::V,
-            //gdk_key::W | gdk_key::w => Key::W,
-            //gdk_key::X | gdk_key::x => Key::X,
-            //gdk_key::Y | gdk_key::y => Key::Y,
-            //gdk_key::Z | gdk_key::z => Key::Z,
-            //gdk_key::bracketleft => Key::LeftBracket,
-            //gdk_key::backslash => Key::Backslash,
-            //gdk_key::bracketright => Key::RightBracket,
-            //gdk_key::dead_grave => Key::GraveAccent,
-            //gdk_key:: => Key::World1, // TODO
-            //gdk_key:: => Key::World2,
-            gdk_key::Escape => Key::Escape,
-            gdk_key::Return => Key::Enter,
-            gdk_key::Tab => Key::Tab,
-            gdk_key::BackSpace => Key::Backspace,
-            gdk_key::Insert => Key::Insert,
-            gdk_key::Delete => Key::Delete,
-            gdk_key::Right => Key::ArrowRight,
-            gdk_key::Left => Key::ArrowLeft,
-            gdk_key::Down => Key::ArrowDown,
-            gdk_key::Up => Key::ArrowUp,
-            gdk_key::Page_Up => Key::PageUp,
-            gdk_key::Page_Down => Key::PageDown,
-            gdk_key::Home => Key::Home,
-            gdk_key::End => Key::End,
-            gdk_key::Caps_Lock => Key::CapsLock,
-            gdk_key::Scroll_Lock => Key::ScrollLock,
-            gdk_key::Num_Lock => Key::NumLock,
-            gdk_key::_3270_PrintScreen => Key::PrintScreen, // TODO: tes)t
-            gdk_key::Pause => Key::Pause,
-            gdk_key::F1 => Key::F1,
-            gdk_key::F2 => Key::F2,
-            gdk_key::F3 => Key::F3,
-            gdk_key::F4 => Key::F4,
-            gdk_key::F5 => Key::F5,
-            gdk_key::F6 => Key::F6,
-            gdk_key::F7 => Key::F7,
-            gdk_key::F8 => Key::F8,
-            gdk_key::F9 => Key::F9,
-            gdk_key::F10 => Key::F10,
-            gdk_key::F11 => Key::F11,
-            gdk_key::F12 => Key::F12,
-            //gdk_key::F13 => Key::F13,
-            //gdk_key::F14 => Key::F14,
-            //gdk_key::F15 => Key::F15,
-            //gdk_key::F16 => Key::F16,
-            //gdk_key::F17 => Key::F17,
-            //gdk_key::F18 => Key::F18,
-            //gdk_key::F19 => Key::F19,
-            //gdk_key::F20 => Key::F20,
-            //gdk_key::F21 => Key::F21,
-            //gdk_key::F22 => Key::F22,
-            //gdk_key::F23 => Key::F23,
-            //gdk_key::F24 => Key::F24,
-            //gdk_key::F25 => Key::F25,
-            //gdk_key::KP_0 => Key::Kp0,
-            //gdk_key::KP_1 => Key::Kp1,
-            //gdk_key::KP_2 => Key::Kp2,
-            //gdk_key::KP_3 => Key::Kp3,
-            //gdk_key::KP_4 => Key::Kp4,
-            //gdk_key::KP_5 => Key::Kp5,
-            //gdk_key::KP_6 => Key::Kp6,
-            //gdk_key::KP_7 => Key::Kp7,
-            //gdk_key::KP_8 => Key::Kp8,
-            //gdk_key::KP_9 => Key::Kp9,
-            //gdk_key::KP_Decimal => Key::KpDecimal,
-            //gdk_key::KP_Divide => Key::KpDivide,
-            //gdk_key::KP_Multiply => Key::KpMultiply,
-            //gdk_key::KP_Subtract => Key::KpSubtract,
-            //gdk_key::KP_Add => Key::KpAdd,
-            //gdk_key::KP_Enter => Key::KpEnter,
-            //gdk_key::KP_Equal => Key::KpEqual,
-            gdk_key::Shift_L => Key::Shift,
-            gdk_key::Control_L => Key::Control,
-            gdk_key::Alt_L => Key::Alt,
-            //gdk_key::Super_L => Key::LeftSuper,
-            gdk_key::Shift_R => Key::Shift,
-            gdk_key::Control_R => Key::Control,
-            gdk_key::Alt_R => Key::Alt,
-            //gdk_key::Super_R => Key::RightSuper,
-            //gdk_key::Menu => Key::Menu,
-            //gdk_key:: => Key::NavigateBackward, // TODO
-            //gdk_key:: => Key::NavigateForward,
-            _ => return
-                /*if let Some(key) = unicode {
-                    (Some(key), Some(Key::A)) // FIXME: don't send A.
-                }
-                else {*/
-                    (None, None)
-                //}
-        };
-    (unicode, Some(key))
+/// Guess the physical key producing `char` on a US layout.
+fn char_code(char: char) -> Code {
+    match char.to_ascii_lowercase() {
+        'a' => Code::KeyA,
+        'b' => Code::KeyB,
+        'c' => Code::KeyC,
+        'd' => Code::KeyD,
+        'e' => Code::KeyE,
+        'f' => Code::KeyF,
+        'g' => Code::KeyG,
+        'h' => Code::KeyH,
+        'i' => Code::KeyI,
+        'j' => Code::KeyJ,
+        'k' => Code::KeyK,
+        'l' => Code::KeyL,
+        'm' => Code::KeyM,
+        'n' => Code::KeyN,
+        'o' => Code::KeyO,
+        'p' => Code::KeyP,
+        'q' => Code::KeyQ,
+        'r' => Code::KeyR,
+        's' => Code::KeyS,
+        't' => Code::KeyT,
+        'u' => Code::KeyU,
+        'v' => Code::KeyV,
+        'w' => Code::KeyW,
+        'x' => Code::KeyX,
+        'y' => Code::KeyY,
+        'z' => Code::KeyZ,
+        '0' | ')' => Code::Digit0,
+        '1' | '!' => Code::Digit1,
+        '2' | '@' => Code::Digit2,
+        '3' | '#' => Code::Digit3,
+        '4' | '$' => Code::Digit4,
+        '5' | '%' => Code::Digit5,
+        '6' | '^' => Code::Digit6,
+        '7' | '&' => Code::Digit7,
+        '8' | '*' => Code::Digit8,
+        '9' | '(' => Code::Digit9,
+        ' ' => Code::Space,
+        '`' | '~' => Code::Backquote,
+        '-' | '_' => Code::Minus,
+        '=' | '+' => Code::Equal,
+        '[' | '{' => Code::BracketLeft,
+        ']' | '}' => Code::BracketRight,
+        '\\' | '|' => Code::Backslash,
+        ';' | ':' => Code::Semicolon,
+        '\'' | '"' => Code::Quote,
+        ',' | '<' => Code::Comma,
+        '.' | '>' => Code::Period,
+        '/' | '?' => Code::Slash,
+        _ => Code::Unidentified,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use gdk::enums::key as gdk_key;
+    use keyboard_types::{Code, Key, Location};
+
+    use super::{char_code, code, key};
+
+    fn character(char: &str) -> Key {
+        Key::Character(char.to_string())
+    }
+
+    #[test]
+    fn printable_keys() {
+        let keys = [
+            (gdk_key::a, character("a"), Code::KeyA),
+            (gdk_key::A, character("A"), Code::KeyA),
+            (gdk_key::_1, character("1"), Code::Digit1),
+            (gdk_key::exclam, character("!"), Code::Digit1),
+            (gdk_key::space, character(" "), Code::Space),
+            (gdk_key::slash, character("/"), Code::Slash),
+            (gdk_key::eacute, character("é"), Code::Unidentified),
+        ];
+        for &(keyval, ref expected_key, expected_code) in keys.iter() {
+            let expected = (expected_key.clone(), expected_code, Location::Standard);
+            assert_eq!(key(keyval), expected, "keyval {:#x}", keyval);
+        }
+    }
+
+    #[test]
+    fn keypad_keys() {
+        let keys = [
+            (gdk_key::KP_0, character("0"), Code::Numpad0),
+            (gdk_key::KP_9, character("9"), Code::Numpad9),
+            (gdk_key::KP_Add, character("+"), Code::NumpadAdd),
+            (gdk_key::KP_Decimal, character("."), Code::NumpadDecimal),
+            (gdk_key::KP_Enter, Key::Enter, Code::NumpadEnter),
+            (gdk_key::KP_Home, Key::Home, Code::Numpad7),
+            (gdk_key::KP_Delete, Key::Delete, Code::NumpadDecimal),
+            (gdk_key::Num_Lock, Key::NumLock, Code::NumLock),
+        ];
+        for &(keyval, ref expected_key, expected_code) in keys.iter() {
+            let expected = (expected_key.clone(), expected_code, Location::Numpad);
+            assert_eq!(key(keyval), expected, "keyval {:#x}", keyval);
+        }
+    }
+
+    #[test]
+    fn function_keys() {
+        assert_eq!(key(gdk_key::F1), (Key::F1, Code::F1, Location::Standard));
+        assert_eq!(key(gdk_key::F12), (Key::F12, Code::F12, Location::Standard));
+        // keyboard-types has no value for F13 to F24, but they must not be taken for characters.
+        let keyvals = [
+            gdk_key::F13, gdk_key::F14, gdk_key::F15, gdk_key::F16, gdk_key::F17, gdk_key::F18,
+            gdk_key::F19, gdk_key::F20, gdk_key::F21, gdk_key::F22, gdk_key::F23, gdk_key::F24,
+        ];
+        for &keyval in keyvals.iter() {
+            let expected = (Key::Unidentified, Code::Unidentified, Location::Standard);
+            assert_eq!(key(keyval), expected, "keyval {:#x}", keyval);
+        }
+    }
+
+    #[test]
+    fn modifier_locations() {
+        let keys = [
+            (gdk_key::Shift_L, Key::Shift, Code::ShiftLeft, Location::Left),
+            (gdk_key::Shift_R, Key::Shift, Code::ShiftRight, Location::Right),
+            (gdk_key::Control_L, Key::Control, Code::ControlLeft, Location::Left),
+            (gdk_key::Control_R, Key::Control, Code::ControlRight, Location::Right),
+            (gdk_key::Alt_L, Key::Alt, Code::AltLeft, Location::Left),
+            (gdk_key::Alt_R, Key::Alt, Code::AltRight, Location::Right),
+            (gdk_key::Super_L, Key::Meta, Code::MetaLeft, Location::Left),
+            (gdk_key::Super_R, Key::Meta, Code::MetaRight, Location::Right),
+            (gdk_key::ISO_Level3_Shift, Key::AltGraph, Code::AltRight, Location::Right),
+            (gdk_key::Menu, Key::ContextMenu, Code::ContextMenu, Location::Standard),
+        ];
+        for &(keyval, ref expected_key, expected_code, expected_location) in keys.iter() {
+            let expected = (expected_key.clone(), expected_code, expected_location);
+            assert_eq!(key(keyval), expected, "keyval {:#x}", keyval);
+        }
+    }
+
+    #[test]
+    fn dead_keys() {
+        let keyvals = [gdk_key::dead_grave, gdk_key::dead_acute, gdk_key::dead_circumflex, gdk_key::dead_diaeresis];
+        for &keyval in keyvals.iter() {
+            assert_eq!(key(keyval), (Key::Dead, Code::Unidentified, Location::Standard), "keyval {:#x}", keyval);
+        }
+    }
+
+    #[test]
+    fn unknown_keys() {
+        let keyvals = [gdk_key::VoidSymbol, 0, 0x00ff_fffe];
+        for &keyval in keyvals.iter() {
+            let expected = (Key::Unidentified, Code::Unidentified, Location::Standard);
+            assert_eq!(key(keyval), expected, "keyval {:#x}", keyval);
+        }
+    }
+
+    #[test]
+    fn char_codes() {
+        let chars = [
+            ('a', Code::KeyA),
+            ('Z', Code::KeyZ),
+            ('0', Code::Digit0),
+            (')', Code::Digit0),
+            ('~', Code::Backquote),
+            ('"', Code::Quote),
+            ('?', Code::Slash),
+            ('é', Code::Unidentified),
+            ('\u{3042}', Code::Unidentified),
+        ];
+        for &(char, expected_code) in chars.iter() {
+            assert_eq!(char_code(char), expected_code, "char {:?}", char);
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn hardware_codes() {
+        let keycodes = [
+            (9, Some(Code::Escape)),
+            (38, Some(Code::KeyA)),
+            (37, Some(Code::ControlLeft)),
+            (105, Some(Code::ControlRight)),
+            (50, Some(Code::ShiftLeft)),
+            (62, Some(Code::ShiftRight)),
+            (90, Some(Code::Numpad0)),
+            (104, Some(Code::NumpadEnter)),
+            (133, Some(Code::MetaLeft)),
+            (0, None),
+            (7, None),
+            (8, None),
+            (u16::max_value(), None),
+        ];
+        for &(keycode, expected_code) in keycodes.iter() {
+            assert_eq!(code(keycode), expected_code, "keycode {}", keycode);
+        }
+    }
 }
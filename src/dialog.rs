@@ -0,0 +1,87 @@
+use std::rc::Rc;
+
+use gtk::{
+    ButtonsType,
+    DialogExt,
+    DialogFlags,
+    GtkWindowExt,
+    MessageDialog,
+    MessageType,
+    ResponseType,
+    WidgetExt,
+    Window,
+};
+
+use reply::Reply;
+
+const BEFORE_UNLOAD_MESSAGE: &str = "This page is asking you to confirm that you want to leave.";
+
+/// The kind of dialog a page asks for.
+///
+/// Servo does not support `confirm()` and `prompt()` yet, so only these dialogs are shown.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ScriptDialogType {
+    /// A message shown with `alert()`.
+    Alert,
+    /// The page asks for a confirmation before being left, with a `beforeunload` handler.
+    BeforeUnloadConfirm,
+}
+
+struct State {
+    dialog_type: ScriptDialogType,
+    message: String,
+    reply: Reply<bool>,
+}
+
+/// A dialog requested by a page.
+///
+/// The page is blocked until the dialog is answered with `confirm()`. When the last clone is
+/// dropped without an answer, the alerts are dismissed and leaving the page is allowed.
+#[derive(Clone)]
+pub struct ScriptDialog {
+    state: Rc<State>,
+}
+
+impl ScriptDialog {
+    pub(crate) fn new<F: Fn(bool) + 'static>(dialog_type: ScriptDialogType, message: String, reply: F) -> Self {
+        ScriptDialog {
+            state: Rc::new(State {
+                dialog_type,
+                message,
+                reply: Reply::new(true, reply),
+            }),
+        }
+    }
+
+    /// Answer the dialog: `false` keeps the user on the page for a `BeforeUnloadConfirm` dialog.
+    /// The value is ignored for alerts.
+    pub fn confirm(&self, confirmed: bool) {
+        self.state.reply.send(confirmed);
+    }
+
+    pub fn get_dialog_type(&self) -> ScriptDialogType {
+        self.state.dialog_type
+    }
+
+    pub fn get_message(&self) -> String {
+        self.state.message.clone()
+    }
+
+    /// Show the dialog as a `MessageDialog`, answering it when the user closes it.
+    pub(crate) fn show(&self, parent: Option<&Window>) {
+        let (message_type, buttons, message) =
+            match self.state.dialog_type {
+                ScriptDialogType::Alert => (MessageType::Info, ButtonsType::Ok, self.state.message.as_str()),
+                ScriptDialogType::BeforeUnloadConfirm =>
+                    (MessageType::Question, ButtonsType::OkCancel, BEFORE_UNLOAD_MESSAGE),
+            };
+        let dialog = MessageDialog::new(parent, DialogFlags::MODAL | DialogFlags::DESTROY_WITH_PARENT, message_type,
+            buttons, message);
+        let script_dialog = self.clone();
+        dialog.connect_response(move |dialog, response| {
+            script_dialog.confirm(response == ResponseType::Ok.into());
+            dialog.destroy();
+        });
+        dialog.present();
+    }
+}
@@ -0,0 +1,56 @@
+use std::error;
+use std::fmt::{self, Display, Formatter};
+use std::path::PathBuf;
+
+#[derive(Debug)]
+pub enum Error {
+    /// The engine was already started: Servo can only run once per process, in a single context.
+    EngineAlreadyStarted,
+    /// Servo has shut down and cannot load anything anymore.
+    EngineShutdown,
+    /// The URL could not be parsed.
+    InvalidUrl(String),
+    /// JavaScript is disabled in the settings of the context.
+    JavascriptDisabled,
+    /// A script run in the page threw an exception, with this name (e.g. `TypeError`) and message.
+    JavascriptException {
+        name: String,
+        message: String,
+    },
+    /// A resource file needed by Servo is neither in the resources directory nor embedded.
+    MissingResource(PathBuf),
+    /// The view has no browser in the engine, e.g. because it was closed.
+    NotReady,
+    /// The page was left before a script run in it reported its result.
+    ScriptAborted,
+}
+
+impl Display for Error {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match *self {
+            Error::EngineAlreadyStarted => write!(formatter, "the engine was already started"),
+            Error::EngineShutdown => write!(formatter, "the engine has shut down"),
+            Error::InvalidUrl(ref error) => write!(formatter, "invalid URL: {}", error),
+            Error::JavascriptDisabled => write!(formatter, "JavaScript is disabled"),
+            Error::JavascriptException { ref name, ref message } => write!(formatter, "{}: {}", name, message),
+            Error::MissingResource(ref path) => write!(formatter, "missing resource {}", path.display()),
+            Error::NotReady => write!(formatter, "the view is not attached to the engine"),
+            Error::ScriptAborted => write!(formatter, "the page was left before the script finished"),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn description(&self) -> &str {
+        match *self {
+            Error::EngineAlreadyStarted => "engine already started",
+            Error::EngineShutdown => "engine shut down",
+            Error::InvalidUrl(_) => "invalid URL",
+            Error::JavascriptDisabled => "JavaScript disabled",
+            Error::JavascriptException { .. } => "JavaScript exception",
+            Error::MissingResource(_) => "missing resource",
+            Error::NotReady => "view not ready",
+            Error::ScriptAborted => "script aborted",
+        }
+    }
+}
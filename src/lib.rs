@@ -1,20 +1,40 @@
 /*
  * TODO: show the gtk scrollbars instead of the Servo scrollbars?
- * TODO: send CloseBrowser event (on tab close?).
  */
 
 extern crate epoxy;
 extern crate gdk;
 extern crate gdk_sys;
+extern crate glib;
 extern crate glib_itc;
 extern crate gtk;
 extern crate keyboard_types;
+extern crate serde_json;
 extern crate servo;
 extern crate shared_library;
 
+mod bridge;
+pub mod context;
 mod convert;
+pub mod dialog;
+mod error;
 mod eventloop;
+pub mod policy;
+mod reply;
+mod resources;
+pub mod settings;
+pub mod uri_scheme;
+pub mod user_content;
 pub mod view;
 mod window;
 
-pub use view::WebView;
+pub use context::WebContext;
+pub use dialog::{ScriptDialog, ScriptDialogType};
+pub use error::Error;
+pub use policy::{NavigationAction, PolicyDecision, PolicyDecisionType};
+pub use servo::servo_config::prefs::PrefValue;
+pub use servo::style_traits::cursor::CursorKind;
+pub use settings::Settings;
+pub use uri_scheme::{UriSchemeRequest, UriSchemeResponse};
+pub use user_content::{InjectionTime, UserContent, UserContentId, UserContentManager};
+pub use view::{GeometryRequest, LoadEvent, WebView, WebViewBuilder};
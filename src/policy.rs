@@ -0,0 +1,112 @@
+/*
+ * Servo does not let the embedder see the requests of the network stack at this revision: the only
+ * requests the embedder can act on are the top-level navigations and the new windows. Servo does
+ * not download files either, so there is no decision for the responses it cannot show.
+ */
+
+use std::rc::Rc;
+
+use gdk::ModifierType;
+
+use reply::Reply;
+
+/// The answer given to Servo for a `PolicyDecision`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Decision {
+    Ignore,
+    Use,
+}
+
+/// What triggered a navigation or a new window.
+#[derive(Clone, Debug)]
+pub struct NavigationAction {
+    button: Option<u32>,
+    modifiers: ModifierType,
+    url: Option<String>,
+    user_gesture: bool,
+}
+
+impl NavigationAction {
+    pub(crate) fn new(url: Option<String>, user_gesture: bool, button: Option<u32>, modifiers: ModifierType) -> Self {
+        NavigationAction {
+            button,
+            modifiers,
+            url,
+            user_gesture,
+        }
+    }
+
+    /// Get the mouse button clicked to trigger the navigation, if any.
+    pub fn get_mouse_button(&self) -> Option<u32> {
+        self.button
+    }
+
+    /// Get the modifiers held during the click or the key press that triggered the navigation.
+    pub fn get_modifiers(&self) -> ModifierType {
+        self.modifiers
+    }
+
+    /// Get the URL of the navigation. Servo does not give it for the new windows.
+    pub fn get_url(&self) -> Option<String> {
+        self.url.clone()
+    }
+
+    /// Whether the user clicked or pressed a key just before the navigation. Servo does not tell,
+    /// so this is a guess.
+    pub fn is_user_gesture(&self) -> bool {
+        self.user_gesture
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PolicyDecisionType {
+    /// A navigation in the top-level document.
+    NavigationAction,
+    /// A request to open a new window, e.g. with `window.open()`.
+    NewWindowAction,
+}
+
+struct State {
+    decision_type: PolicyDecisionType,
+    navigation_action: NavigationAction,
+    reply: Reply<Decision>,
+}
+
+/// A decision to take about a navigation or a new window.
+///
+/// The decision can be kept to be taken later. When the last clone is dropped without a decision,
+/// the navigation or the new window is allowed.
+#[derive(Clone)]
+pub struct PolicyDecision {
+    state: Rc<State>,
+}
+
+impl PolicyDecision {
+    pub(crate) fn new<F>(decision_type: PolicyDecisionType, navigation_action: NavigationAction, reply: F) -> Self
+        where F: Fn(Decision) + 'static,
+    {
+        PolicyDecision {
+            state: Rc::new(State {
+                decision_type,
+                navigation_action,
+                reply: Reply::new(Decision::Use, reply),
+            }),
+        }
+    }
+
+    pub fn get_decision_type(&self) -> PolicyDecisionType {
+        self.state.decision_type
+    }
+
+    pub fn get_navigation_action(&self) -> NavigationAction {
+        self.state.navigation_action.clone()
+    }
+
+    pub fn ignore(&self) {
+        self.state.reply.send(Decision::Ignore);
+    }
+
+    pub fn use_(&self) {
+        self.state.reply.send(Decision::Use);
+    }
+}
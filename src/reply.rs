@@ -0,0 +1,33 @@
+use std::cell::RefCell;
+
+/// The answer a page waits for, e.g. to a dialog: it is sent at most once, and the default one is
+/// sent when it is dropped unanswered, so that the page is never left hanging.
+pub struct Reply<T> {
+    default: Option<T>,
+    reply: RefCell<Option<Box<Fn(T)>>>,
+}
+
+impl<T> Reply<T> {
+    pub fn new<F: Fn(T) + 'static>(default: T, reply: F) -> Self {
+        Reply {
+            default: Some(default),
+            reply: RefCell::new(Some(Box::new(reply))),
+        }
+    }
+
+    /// Send `answer`, unless an answer was already sent.
+    pub fn send(&self, answer: T) {
+        let reply = self.reply.borrow_mut().take();
+        if let Some(reply) = reply {
+            reply(answer);
+        }
+    }
+}
+
+impl<T> Drop for Reply<T> {
+    fn drop(&mut self) {
+        if let (Some(reply), Some(default)) = (self.reply.get_mut().take(), self.default.take()) {
+            reply(default);
+        }
+    }
+}
@@ -0,0 +1,181 @@
+use std::collections::VecDeque;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use servo::embedder_traits::resources::{Resource, ResourceReaderMethods};
+
+use bridge;
+use error::Error;
+use settings::Settings;
+
+/// Environment variable used to find the resources directory when it is not set on the context.
+pub const RESOURCES_DIR_VAR: &str = "SERVO_GTK_RESOURCES";
+
+/// How many error pages can wait for their report to be received: the oldest nonces are dropped.
+const MAX_ERROR_PAGE_NONCES: usize = 16;
+
+const RESOURCES: [Resource; 12] = [
+    Resource::Preferences,
+    Resource::BluetoothBlocklist,
+    Resource::DomainList,
+    Resource::HstsPreloadList,
+    Resource::SSLCertificates,
+    Resource::BadCertHTML,
+    Resource::NetErrorHTML,
+    Resource::UserAgentCSS,
+    Resource::ServoCSS,
+    Resource::PresentationalHintsCSS,
+    Resource::QuirksModeCSS,
+    Resource::RippyPNG,
+];
+
+pub struct ResourceReader {
+    dir: Option<PathBuf>,
+    /// The errors to report to the context, see `WebContext::connect_error()`.
+    errors: Arc<Mutex<Vec<Error>>>,
+    /// The nonces given to the error pages and not used yet, see `bridge::error_page_script()`.
+    nonces: Arc<Mutex<VecDeque<String>>>,
+    settings: Settings,
+}
+
+impl ResourceReader {
+    pub fn new(dir: Option<PathBuf>, settings: Settings, nonces: Arc<Mutex<VecDeque<String>>>,
+        errors: Arc<Mutex<Vec<Error>>>) -> Self
+    {
+        ResourceReader {
+            dir,
+            errors,
+            nonces,
+            settings,
+        }
+    }
+
+    /// Make a nonce for a new error page, so that the nonce of a page cannot be reused.
+    fn error_page_nonce(&self) -> String {
+        let nonce = bridge::nonce();
+        let mut nonces = self.nonces.lock().unwrap();
+        if nonces.len() == MAX_ERROR_PAGE_NONCES {
+            nonces.pop_front();
+        }
+        nonces.push_back(nonce.clone());
+        nonce
+    }
+}
+
+impl ResourceReaderMethods for ResourceReader {
+    fn read(&self, file: Resource) -> Vec<u8> {
+        let is_error_page =
+            match file {
+                Resource::BadCertHTML | Resource::NetErrorHTML => true,
+                _ => false,
+            };
+        let mut content =
+            match read(self.dir.as_ref().map(PathBuf::as_path), &file) {
+                Ok(content) => content,
+                Err(error) => {
+                    // The resources are checked before Servo is started, so the file was removed
+                    // since, and Servo cannot be told about the error.
+                    self.errors.lock().unwrap().push(error);
+                    vec![]
+                },
+            };
+        if is_error_page {
+            // Let the error page report the failure to the view.
+            content.extend_from_slice(bridge::error_page_script(&self.error_page_nonce()).as_bytes());
+        }
+        if let Resource::Preferences = file {
+            content = self.settings.merge_into(&content);
+        }
+        content
+    }
+    fn sandbox_access_files_dirs(&self) -> Vec<PathBuf> {
+        self.dir.iter().cloned().collect()
+    }
+    fn sandbox_access_files(&self) -> Vec<PathBuf> {
+        vec![]
+    }
+}
+
+/// Check that every resource can be read from `dir`, or from the embedded copies.
+pub fn check(dir: Option<&Path>) -> Result<(), Error> {
+    for file in RESOURCES.iter() {
+        let in_dir = dir.map(|dir| dir.join(filename(file)).is_file()).unwrap_or(false);
+        if !in_dir && embedded(file).is_none() {
+            return Err(Error::MissingResource(path(dir, file)));
+        }
+    }
+    Ok(())
+}
+
+/// Find the resources directory from the environment variable or the current directory.
+pub fn default_dir() -> Option<PathBuf> {
+    if let Some(dir) = env::var_os(RESOURCES_DIR_VAR) {
+        return Some(PathBuf::from(dir));
+    }
+    env::current_dir().ok()
+        .map(|dir| dir.join("resources"))
+        .filter(|dir| dir.is_dir())
+}
+
+#[cfg(feature = "embedded-resources")]
+fn embedded(file: &Resource) -> Option<&'static [u8]> {
+    let content: &'static [u8] =
+        match file {
+            Resource::Preferences => include_bytes!("../resources/prefs.json"),
+            Resource::BluetoothBlocklist => include_bytes!("../resources/gatt_blocklist.txt"),
+            Resource::DomainList => include_bytes!("../resources/public_domains.txt"),
+            Resource::HstsPreloadList => include_bytes!("../resources/hsts_preload.json"),
+            Resource::SSLCertificates => include_bytes!("../resources/certs"),
+            Resource::BadCertHTML => include_bytes!("../resources/badcert.html"),
+            Resource::NetErrorHTML => include_bytes!("../resources/neterror.html"),
+            Resource::UserAgentCSS => include_bytes!("../resources/user-agent.css"),
+            Resource::ServoCSS => include_bytes!("../resources/servo.css"),
+            Resource::PresentationalHintsCSS => include_bytes!("../resources/presentational-hints.css"),
+            Resource::QuirksModeCSS => include_bytes!("../resources/quirks-mode.css"),
+            Resource::RippyPNG => include_bytes!("../resources/rippy.png"),
+        };
+    Some(content)
+}
+
+#[cfg(not(feature = "embedded-resources"))]
+fn embedded(_file: &Resource) -> Option<&'static [u8]> {
+    None
+}
+
+fn filename(file: &Resource) -> &'static str {
+    match file {
+        Resource::Preferences => "prefs.json",
+        Resource::BluetoothBlocklist => "gatt_blocklist.txt",
+        Resource::DomainList => "public_domains.txt",
+        Resource::HstsPreloadList => "hsts_preload.json",
+        Resource::SSLCertificates => "certs",
+        Resource::BadCertHTML => "badcert.html",
+        Resource::NetErrorHTML => "neterror.html",
+        Resource::UserAgentCSS => "user-agent.css",
+        Resource::ServoCSS => "servo.css",
+        Resource::PresentationalHintsCSS => "presentational-hints.css",
+        Resource::QuirksModeCSS => "quirks-mode.css",
+        Resource::RippyPNG => "rippy.png",
+    }
+}
+
+/// Read a resource from `dir`, falling back to the embedded copy.
+pub fn read(dir: Option<&Path>, file: &Resource) -> Result<Vec<u8>, Error> {
+    if let Some(dir) = dir {
+        if let Ok(content) = fs::read(dir.join(filename(file))) {
+            return Ok(content);
+        }
+    }
+    embedded(file)
+        .map(|content| content.to_vec())
+        .ok_or_else(|| Error::MissingResource(path(dir, file)))
+}
+
+fn path(dir: Option<&Path>, file: &Resource) -> PathBuf {
+    match dir {
+        Some(dir) => dir.join(filename(file)),
+        None => PathBuf::from(filename(file)),
+    }
+}
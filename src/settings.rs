@@ -0,0 +1,208 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use serde_json::{self, Map, Number, Value};
+use servo::embedder_traits::resources::Resource;
+use servo::servo_config::opts;
+use servo::servo_config::prefs::{PrefValue, PREFS};
+
+use resources;
+
+const DEFAULT_FONT_SIZE: &str = "fonts.default-size";
+const DEFAULT_MONOSPACE_FONT_SIZE: &str = "fonts.default-monospace-size";
+const ENABLE_JAVASCRIPT: &str = "js.enabled";
+const ENABLE_WEBGL: &str = "dom.webgl.enabled";
+const MINIMUM_FONT_SIZE: &str = "fonts.minimum-size";
+const MONOSPACE_FONT_FAMILY: &str = "fonts.monospace";
+const SANS_SERIF_FONT_FAMILY: &str = "fonts.sans-serif";
+const SERIF_FONT_FAMILY: &str = "fonts.serif";
+const STANDARD_FONT_FAMILY: &str = "fonts.default";
+
+struct State {
+    /// Set once Servo has loaded its preferences: from then on, changes go to Servo directly.
+    live: AtomicBool,
+    overrides: Mutex<HashMap<String, PrefValue>>,
+    /// Where `prefs.json` is read from until Servo has loaded it.
+    resources_dir: Mutex<Option<PathBuf>>,
+    user_agent: Mutex<Option<String>>,
+}
+
+/// Engine preferences of a `WebContext`, applied on top of `prefs.json`.
+///
+/// Servo keeps a single set of preferences for the whole process: only the settings of the context
+/// that starts the engine are applied, and the changes made afterwards apply to all the views.
+#[derive(Clone)]
+pub struct Settings {
+    state: Arc<State>,
+}
+
+impl Settings {
+    pub(crate) fn new(resources_dir: Option<PathBuf>) -> Self {
+        Settings {
+            state: Arc::new(State {
+                live: AtomicBool::new(false),
+                overrides: Mutex::new(HashMap::new()),
+                resources_dir: Mutex::new(resources_dir),
+                user_agent: Mutex::new(None),
+            }),
+        }
+    }
+
+    /// Apply the settings Servo only reads at startup. Must be called before the engine is created.
+    pub(crate) fn apply_startup_options(&self) {
+        if let Some(ref user_agent) = *self.state.user_agent.lock().unwrap() {
+            let mut options = opts::default_opts();
+            options.user_agent = user_agent.clone().into();
+            opts::set_options(options);
+        }
+    }
+
+    /// Layer the overridden preferences on top of the content of `prefs.json`.
+    pub(crate) fn merge_into(&self, prefs: &[u8]) -> Vec<u8> {
+        let mut json =
+            match serde_json::from_slice(prefs) {
+                Ok(Value::Object(json)) => json,
+                _ => Map::new(),
+            };
+        for (name, value) in self.state.overrides.lock().unwrap().iter() {
+            if let Some(value) = to_json(value) {
+                json.insert(name.clone(), value);
+            }
+        }
+        serde_json::to_vec(&json).unwrap_or_else(|_| prefs.to_vec())
+    }
+
+    pub(crate) fn set_resources_dir(&self, dir: PathBuf) {
+        *self.state.resources_dir.lock().unwrap() = Some(dir);
+    }
+
+    pub(crate) fn set_live(&self) {
+        self.state.live.store(true, Ordering::SeqCst);
+    }
+
+    pub fn get_default_font_size(&self) -> u32 {
+        self.get_pref(DEFAULT_FONT_SIZE).as_i64().unwrap_or(16) as u32
+    }
+
+    pub fn get_default_monospace_font_size(&self) -> u32 {
+        self.get_pref(DEFAULT_MONOSPACE_FONT_SIZE).as_i64().unwrap_or(13) as u32
+    }
+
+    pub fn get_enable_javascript(&self) -> bool {
+        self.get_pref(ENABLE_JAVASCRIPT).as_boolean().unwrap_or(true)
+    }
+
+    pub fn get_enable_webgl(&self) -> bool {
+        self.get_pref(ENABLE_WEBGL).as_boolean().unwrap_or(true)
+    }
+
+    pub fn get_minimum_font_size(&self) -> u32 {
+        self.get_pref(MINIMUM_FONT_SIZE).as_i64().unwrap_or(0) as u32
+    }
+
+    pub fn get_monospace_font_family(&self) -> Option<String> {
+        self.get_pref(MONOSPACE_FONT_FAMILY).as_string().map(str::to_string)
+    }
+
+    /// Get the value of a preference, as overridden or as read by Servo. Before the engine is
+    /// started, the value is read from `prefs.json`.
+    pub fn get_pref(&self, name: &str) -> PrefValue {
+        if let Some(value) = self.state.overrides.lock().unwrap().get(name) {
+            return value.clone();
+        }
+        if self.state.live.load(Ordering::SeqCst) {
+            return (*PREFS.get(name)).clone();
+        }
+        let resources_dir = self.state.resources_dir.lock().unwrap().clone();
+        resources::read(resources_dir.as_ref().map(PathBuf::as_path), &Resource::Preferences).ok()
+            .and_then(|prefs| serde_json::from_slice::<Map<String, Value>>(&prefs).ok())
+            .and_then(|prefs| prefs.get(name).map(from_json))
+            .unwrap_or(PrefValue::Missing)
+    }
+
+    pub fn get_sans_serif_font_family(&self) -> Option<String> {
+        self.get_pref(SANS_SERIF_FONT_FAMILY).as_string().map(str::to_string)
+    }
+
+    pub fn get_serif_font_family(&self) -> Option<String> {
+        self.get_pref(SERIF_FONT_FAMILY).as_string().map(str::to_string)
+    }
+
+    pub fn get_standard_font_family(&self) -> Option<String> {
+        self.get_pref(STANDARD_FONT_FAMILY).as_string().map(str::to_string)
+    }
+
+    pub fn get_user_agent(&self) -> Option<String> {
+        self.state.user_agent.lock().unwrap().clone()
+    }
+
+    pub fn set_default_font_size(&self, size: u32) {
+        self.set_pref(DEFAULT_FONT_SIZE, PrefValue::Number(size as f64));
+    }
+
+    pub fn set_default_monospace_font_size(&self, size: u32) {
+        self.set_pref(DEFAULT_MONOSPACE_FONT_SIZE, PrefValue::Number(size as f64));
+    }
+
+    pub fn set_enable_javascript(&self, enabled: bool) {
+        self.set_pref(ENABLE_JAVASCRIPT, PrefValue::Boolean(enabled));
+    }
+
+    pub fn set_enable_webgl(&self, enabled: bool) {
+        self.set_pref(ENABLE_WEBGL, PrefValue::Boolean(enabled));
+    }
+
+    pub fn set_minimum_font_size(&self, size: u32) {
+        self.set_pref(MINIMUM_FONT_SIZE, PrefValue::Number(size as f64));
+    }
+
+    pub fn set_monospace_font_family(&self, family: &str) {
+        self.set_pref(MONOSPACE_FONT_FAMILY, PrefValue::String(family.to_string()));
+    }
+
+    /// Set any preference Servo reads from `prefs.json`.
+    pub fn set_pref(&self, name: &str, value: PrefValue) {
+        self.state.overrides.lock().unwrap().insert(name.to_string(), value.clone());
+        if self.state.live.load(Ordering::SeqCst) {
+            PREFS.set(name, value);
+        }
+    }
+
+    pub fn set_sans_serif_font_family(&self, family: &str) {
+        self.set_pref(SANS_SERIF_FONT_FAMILY, PrefValue::String(family.to_string()));
+    }
+
+    pub fn set_serif_font_family(&self, family: &str) {
+        self.set_pref(SERIF_FONT_FAMILY, PrefValue::String(family.to_string()));
+    }
+
+    pub fn set_standard_font_family(&self, family: &str) {
+        self.set_pref(STANDARD_FONT_FAMILY, PrefValue::String(family.to_string()));
+    }
+
+    /// Servo only reads the user agent at startup, so this has no effect once the first view of
+    /// the context is realized.
+    pub fn set_user_agent(&self, user_agent: &str) {
+        *self.state.user_agent.lock().unwrap() = Some(user_agent.to_string());
+    }
+}
+
+fn from_json(value: &Value) -> PrefValue {
+    match *value {
+        Value::Bool(value) => PrefValue::Boolean(value),
+        Value::Number(ref value) => value.as_f64().map(PrefValue::Number).unwrap_or(PrefValue::Missing),
+        Value::String(ref value) => PrefValue::String(value.clone()),
+        _ => PrefValue::Missing,
+    }
+}
+
+fn to_json(value: &PrefValue) -> Option<Value> {
+    match *value {
+        PrefValue::Boolean(value) => Some(Value::Bool(value)),
+        PrefValue::Number(value) => Number::from_f64(value).map(Value::Number),
+        PrefValue::String(ref value) => Some(Value::String(value.clone())),
+        PrefValue::Missing => None,
+    }
+}
@@ -0,0 +1,83 @@
+/*
+ * Servo's network stack cannot be extended at this revision, so the custom schemes are handled in
+ * the embedder: the top-level navigations to those URLs are replaced by a load of the response as a
+ * data: URL. This means that:
+ *  - the subresources of the documents never reach the handlers, and their relative URLs resolve
+ *    against the data: URL, so they fail to load;
+ *  - the documents have an opaque origin;
+ *  - the responses have no status nor headers;
+ *  - the bodies cannot be streamed.
+ */
+
+use std::io::{self, Read};
+
+use servo::servo_url::ServoUrl;
+
+use convert;
+
+/// A navigation to a URL of a scheme registered with `WebContext::register_uri_scheme()`.
+pub struct UriSchemeRequest {
+    url: ServoUrl,
+}
+
+impl UriSchemeRequest {
+    pub(crate) fn new(url: ServoUrl) -> Self {
+        UriSchemeRequest {
+            url,
+        }
+    }
+
+    /// Get the path of the URL, e.g. `/index.html` for `app://host/index.html`.
+    pub fn get_path(&self) -> String {
+        self.url.path().to_string()
+    }
+
+    pub fn get_scheme(&self) -> String {
+        self.url.scheme().to_string()
+    }
+
+    pub fn get_url(&self) -> String {
+        self.url.as_str().to_string()
+    }
+}
+
+/// The document returned for a `UriSchemeRequest`.
+///
+/// The response is loaded as a `data:` URL, which has no status nor headers: only the MIME type, the
+/// charset and the body reach Servo.
+pub struct UriSchemeResponse {
+    body: Vec<u8>,
+    charset: Option<String>,
+    mime_type: String,
+}
+
+impl UriSchemeResponse {
+    pub fn new(mime_type: &str, body: Vec<u8>) -> Self {
+        UriSchemeResponse {
+            body,
+            charset: None,
+            mime_type: mime_type.to_string(),
+        }
+    }
+
+    /// Set the encoding of the body, e.g. `utf-8`. Without it, Servo decodes text as windows-1252,
+    /// unless the document declares its encoding.
+    pub fn charset(mut self, charset: &str) -> Self {
+        self.charset = Some(charset.to_string());
+        self
+    }
+
+    /// Read the whole body from `reader`: the document is only loaded once it is complete.
+    pub fn from_reader<R: Read>(mime_type: &str, mut reader: R) -> io::Result<Self> {
+        let mut body = vec![];
+        reader.read_to_end(&mut body)?;
+        Ok(Self::new(mime_type, body))
+    }
+
+    pub(crate) fn to_data_url(&self) -> String {
+        let charset = self.charset.as_ref()
+            .map(|charset| format!(";charset={}", charset))
+            .unwrap_or_default();
+        format!("data:{}{},{}", self.mime_type, charset, convert::percent_encode(&self.body))
+    }
+}
@@ -1,101 +1,131 @@
 use std::cell::{Cell, RefCell};
-use std::env;
-use std::fs;
-use std::io;
-use std::path::PathBuf;
-use std::ptr;
-use std::rc::Rc;
-use std::sync::{Arc, Mutex, Once, ONCE_INIT};
-
-use epoxy;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::mem;
+use std::rc::{Rc, Weak};
+use std::time::{Duration, Instant};
+
 use gdk::{
+    Cursor,
+    Display,
+    Event,
+    EventKey,
     EventMask,
+    EventScroll,
+    EventSequence,
+    EventTouch,
+    EventType,
+    InputSource,
+    Rectangle,
     ScrollDirection,
+    WindowExt,
+    SELECTION_CLIPBOARD,
+    SELECTION_PRIMARY,
 };
 use gdk::ModifierType;
-use glib_itc::{Receiver, channel};
+use glib::SignalHandlerId;
+use gtk;
 use gtk::{
+    Cast,
+    Clipboard,
     Continue,
     GLArea,
     GLAreaExt,
+    GestureExt,
+    GestureZoom,
+    GestureZoomExt,
+    IMContextExt,
+    IMMulticontext,
     Inhibit,
+    ObjectExt,
     WidgetExt,
+    Window,
 };
-use keyboard_types::{Key, KeyboardEvent};
+use keyboard_types::{Code, Key, KeyState, KeyboardEvent, Modifiers};
+use serde_json::Value;
 use servo;
 use servo::BrowserId;
 use servo::compositing::windowing::{MouseWindowEvent, WindowEvent};
-use servo::embedder_traits::resources::{self, Resource};
+use servo::embedder_traits::EmbedderMsg;
 use servo::euclid::{TypedPoint2D, TypedVector2D};
-use servo::gl;
 use servo::ipc_channel::ipc;
 use servo::msg::constellation_msg::{TraversalDirection};
-use servo::script_traits::TouchEventType;
+use servo::net_traits::net_error_list::NetError;
+use servo::script_traits::{MouseButton, TouchEventType, TouchId};
 use servo::servo_url::ServoUrl;
-use shared_library::dynamic_library::DynamicLibrary;
+use servo::style_traits::cursor::CursorKind;
+use servo::webrender_api::ScrollLocation;
 
+use bridge::{self, Message};
+use context::WebContext;
 use convert;
-use eventloop::GtkEventLoopWaker;
-use window::GtkWindow;
-
-macro_rules! with_servo {
-    ($_self:ident, | $browser_id:ident, $servo:ident | $block:block, no_activate) => {
-        let mut state = $_self.state.borrow_mut();
-        if let Some($browser_id) = state.browser_id.clone() {
-            if let Some(ref mut servo) = state.servo {
-                let mut $servo = servo.borrow_mut();
-                $block
-            }
+use dialog::{ScriptDialog, ScriptDialogType};
+use policy::{
+    Decision,
+    NavigationAction,
+    PolicyDecision,
+    PolicyDecisionType,
+};
+use user_content::{self, InjectionTime, UserContentManager};
+use error::Error;
+
+macro_rules! with_browser {
+    ($_self:ident, | $browser_id:ident | $block:block, no_activate) => {
+        let browser_id = $_self.state.borrow().browser_id.clone();
+        if let Some($browser_id) = browser_id {
+            $block
         }
     };
-    ($_self:ident, | $browser_id:ident, $servo:ident | $block:block) => {
+    ($_self:ident, | $browser_id:ident | $block:block) => {
         $_self.activate();
-        with_servo!($_self, |$browser_id, $servo| $block, no_activate);
+        with_browser!($_self, |$browser_id| $block, no_activate);
     };
 }
 
-static EPOXY_INIT: Once = ONCE_INIT;
+pub type View = GLArea;
 
-struct ResourceReader;
+const BUTTON_BACK: u32 = 8;
+const BUTTON_FORWARD: u32 = 9;
 
-impl resources::ResourceReaderMethods for ResourceReader {
-    fn read(&self, file: Resource) -> Vec<u8> {
-        let file = filename(file);
-        let mut path = resources_dir_path().expect("Can't find resources directory");
-        path.push(file);
-        fs::read(path).expect("Can't read file")
-    }
-    fn sandbox_access_files_dirs(&self) -> Vec<PathBuf> {
-        vec![resources_dir_path().expect("Can't find resources directory")]
-    }
-    fn sandbox_access_files(&self) -> Vec<PathBuf> {
-        vec![]
-    }
-}
+const DEFAULT_LINE_SIZE: f32 = 38.0;
+const DEFAULT_PAGE_SIZE_RATIO: f32 = 0.875;
 
-fn filename(file: Resource) -> &'static str {
-    match file {
-        Resource::Preferences => "prefs.json",
-        Resource::BluetoothBlocklist => "gatt_blocklist.txt",
-        Resource::DomainList => "public_domains.txt",
-        Resource::HstsPreloadList => "hsts_preload.json",
-        Resource::SSLCertificates => "certs",
-        Resource::BadCertHTML => "badcert.html",
-        Resource::NetErrorHTML => "neterror.html",
-        Resource::UserAgentCSS => "user-agent.css",
-        Resource::ServoCSS => "servo.css",
-        Resource::PresentationalHintsCSS => "presentational-hints.css",
-        Resource::QuirksModeCSS => "quirks-mode.css",
-        Resource::RippyPNG => "rippy.png",
-    }
-}
+/// How long after a click or a key press a navigation is considered to come from the user.
+const USER_GESTURE_DELAY_MS: u64 = 1000;
+
+/// How long to wait for the page to be left before closing it anyway.
+const CLOSE_TIMEOUT_MS: u32 = 1000;
+
+/// How many loads of the view can wait for their navigation to be allowed.
+const MAX_INTERNAL_LOADS: usize = 16;
 
-fn resources_dir_path() -> io::Result<PathBuf> {
-    let path = env::current_dir().unwrap().join("resources");
-    Ok(path)
+const KINETIC_FRAME_MS: u32 = 16;
+const KINETIC_FRICTION: f32 = 0.95;
+const KINETIC_MIN_DELTA: f32 = 0.5;
+
+/// A change of the window asked by the page, e.g. with `window.resizeTo()`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GeometryRequest {
+    Move {
+        x: i32,
+        y: i32,
+    },
+    Resize {
+        width: i32,
+        height: i32,
+    },
 }
 
-pub type View = GLArea;
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LoadEvent {
+    /// A new page started to load.
+    Started,
+    /// The page started to receive its content: the URL is now the one of the new page.
+    Committed,
+    /// The page and its subresources were loaded.
+    Finished,
+}
 
 struct Pos {
     x: f64,
@@ -113,131 +143,634 @@ impl Pos {
 
 struct State {
     browser_id: Option<BrowserId>,
+    can_go_back: bool,
+    can_go_forward: bool,
+    /// Changed by every close(), and when the page asks to confirm it is left: only the last close
+    /// is forced when the page is not left in time.
+    close_generation: u32,
+    close_requested_callback: Option<Rc<Fn()>>,
+    /// Set once the browser is closed, or being closed.
+    closed: bool,
+    /// Set while the page is being left before being closed.
+    closing: bool,
+    composing: bool,
+    context: WebContext,
+    create_callback: Option<Rc<Fn(&NavigationAction) -> Option<WebView>>>,
+    cursor_changed_callback: Option<Rc<Fn(CursorKind) -> Inhibit>>,
+    /// The cursors already created, by name.
+    cursors: HashMap<&'static str, Cursor>,
+    decide_policy_callback: Option<Rc<Fn(&PolicyDecision)>>,
+    destroyed_callback: Option<Rc<Fn()>>,
+    filtered_key: Option<KeyboardEvent>,
+    geometry_requested_callback: Option<Rc<Fn(GeometryRequest)>>,
+    /// The handlers connected to the widget, disconnected when the view is destroyed.
+    handlers: Vec<SignalHandlerId>,
+    hovered_link: Option<String>,
+    im_context: IMMulticontext,
+    im_handlers: Vec<SignalHandlerId>,
+    /// The hashes of the URLs loaded by load_internal() whose navigation was not allowed yet.
+    internal_loads: VecDeque<u64>,
+    kinetic_generation: u32,
+    /// Where the user clicked last, to place the candidate window of the input method.
+    last_click: Pos,
+    /// The time of the last click or key press, the button clicked and the modifiers held.
+    last_input: Option<(Instant, Option<u32>, ModifierType)>,
+    load_changed_callback: Option<Rc<Fn(LoadEvent)>>,
+    load_event: Option<LoadEvent>,
+    load_failed_callback: Option<Rc<Fn(String, NetError)>>,
+    /// The URL of the last top-level navigation allowed, which is not committed if it fails.
+    loading_url: Option<String>,
+    /// The message handlers and the URL patterns of the pages allowed to use them, by name.
+    message_handlers: HashMap<String, (Vec<String>, Rc<Fn(Value) -> Option<Value>>)>,
+    /// The token of the messages of the current document, once it has `window.servoGtk`.
+    message_token: Option<String>,
+    mouse_target_changed_callback: Option<Rc<Fn(Option<String>)>>,
+    navigation_buttons: bool,
+    next_script_id: u64,
+    next_touch_id: i32,
+    /// Set when the next paste must take the text from the PRIMARY selection.
+    paste_primary: bool,
+    pending_url: Option<ServoUrl>,
+    pinch_gesture: Option<GestureZoom>,
+    pinch_scale: f64,
+    /// Set while a pinch is recognized: the touches are not given to Servo, which would zoom too.
+    pinching: bool,
     pointer: Pos,
-    rx: Receiver<()>,
-    servo: Option<Rc<RefCell<servo::Servo<GtkWindow>>>>,
+    /// Set once the view is realized and its events connected.
+    prepared: bool,
+    pressed_keys: HashSet<u16>,
+    /// The callbacks of the scripts run with run_javascript() and the token of their reports, by id.
+    script_callbacks: HashMap<u64, (String, Rc<Fn(Result<Value, Error>)>)>,
+    script_dialog_callback: Option<Rc<Fn(&ScriptDialog) -> Inhibit>>,
+    /// The javascript: URLs loaded by run_in_page() whose LoadStart has not arrived yet: they do not
+    /// leave the page.
+    script_loads: u32,
+    scroll_line_size: f32,
+    scroll_page_size: Option<f32>,
+    scroll_velocity: (f32, f32),
+    scrolling: bool,
+    title: Option<String>,
+    title_callback: Option<Rc<Fn(Option<String>)>>,
+    /// The touch point ids given to Servo and their last position, by GDK sequence.
+    touches: Vec<(EventSequence, TouchId, Pos)>,
+    /// The URLs of the registered schemes, by the data: URL loaded for them.
+    uri_scheme_urls: HashMap<u64, String>,
+    url: Option<String>,
+    url_callback: Option<Rc<Fn(String)>>,
+    user_content_manager: UserContentManager,
     view: View,
-    window: Rc<GtkWindow>,
     zoom_level: Cell<f32>,
 }
 
+/// A widget showing web pages with Servo.
+///
+/// Servo cannot show the text an input method is composing (the preedit): the text only appears in
+/// the page once committed, and the window of the input method is the only feedback until then.
 #[derive(Clone)]
 pub struct WebView {
     state: Rc<RefCell<State>>,
 }
 
-impl WebView {
-    pub fn new() -> Self {
-        let view = GLArea::new();
-        view.set_auto_render(false);
-        view.set_has_depth_buffer(true);
-        view.add_events((EventMask::BUTTON_PRESS_MASK | EventMask::BUTTON_RELEASE_MASK | EventMask::POINTER_MOTION_MASK
-            | EventMask::SCROLL_MASK).bits() as i32);
-        view.set_can_focus(true);
-        view.set_size_request(200, 200);
-
-        EPOXY_INIT.call_once(|| {
-            epoxy::load_with(|s| {
-                unsafe {
-                    match DynamicLibrary::open(None).unwrap().symbol(s) {
-                        Ok(v) => v,
-                        Err(_) => ptr::null(),
-                    }
-                }
-            });
-        });
+const DEFAULT_URL: &str = "https://servo.org";
 
-        let gl = unsafe {
-            gl::GlFns::load_with(epoxy::get_proc_addr)
-        };
+/// Options used to create a `WebView`.
+pub struct WebViewBuilder {
+    can_focus: bool,
+    context: Option<WebContext>,
+    navigation_buttons: bool,
+    size_request: (i32, i32),
+    url: String,
+    user_content_manager: Option<UserContentManager>,
+    zoom_level: f32,
+}
 
-        let (tx, rx) = channel();
+impl WebViewBuilder {
+    pub fn new() -> Self {
+        WebViewBuilder {
+            can_focus: true,
+            context: None,
+            navigation_buttons: true,
+            size_request: (200, 200),
+            url: DEFAULT_URL.to_string(),
+            user_content_manager: None,
+            zoom_level: 1.0,
+        }
+    }
 
-        let waker = Box::new(GtkEventLoopWaker::new(Arc::new(Mutex::new(tx))));
+    pub fn build(self) -> Result<WebView, Error> {
+        let url = ServoUrl::parse(&self.url)
+            .map_err(|error| Error::InvalidUrl(format!("{}: {}", self.url, error)))?;
+        let context = self.context.unwrap_or_else(WebContext::get_default);
 
-        let window = Rc::new(GtkWindow::new(gl, view.clone(), waker));
+        let view = GLArea::new();
+        view.set_auto_render(false);
+        view.set_has_depth_buffer(true);
+        view.add_events((EventMask::BUTTON_PRESS_MASK | EventMask::BUTTON_RELEASE_MASK | EventMask::KEY_PRESS_MASK
+            | EventMask::KEY_RELEASE_MASK | EventMask::POINTER_MOTION_MASK | EventMask::SCROLL_MASK
+            | EventMask::SMOOTH_SCROLL_MASK | EventMask::TOUCH_MASK | EventMask::ENTER_NOTIFY_MASK
+            | EventMask::LEAVE_NOTIFY_MASK).bits() as i32);
+        view.set_can_focus(self.can_focus);
+        view.set_size_request(self.size_request.0, self.size_request.1);
 
         let state = Rc::new(RefCell::new(State {
             browser_id: None,
+            can_go_back: false,
+            can_go_forward: false,
+            close_generation: 0,
+            close_requested_callback: None,
+            closed: false,
+            closing: false,
+            composing: false,
+            context,
+            create_callback: None,
+            cursor_changed_callback: None,
+            cursors: HashMap::new(),
+            decide_policy_callback: None,
+            destroyed_callback: None,
+            filtered_key: None,
+            geometry_requested_callback: None,
+            handlers: vec![],
+            hovered_link: None,
+            im_context: IMMulticontext::new(),
+            im_handlers: vec![],
+            internal_loads: VecDeque::new(),
+            kinetic_generation: 0,
+            last_click: Pos::new(0.0, 0.0),
+            last_input: None,
+            load_changed_callback: None,
+            load_event: None,
+            load_failed_callback: None,
+            loading_url: None,
+            message_handlers: HashMap::new(),
+            message_token: None,
+            mouse_target_changed_callback: None,
+            navigation_buttons: self.navigation_buttons,
+            next_script_id: 0,
+            next_touch_id: 0,
+            paste_primary: false,
+            pending_url: Some(url),
+            pinch_gesture: None,
+            pinch_scale: 1.0,
+            pinching: false,
             pointer: Pos::new(0.0, 0.0),
-            rx,
-            servo: None,
+            prepared: false,
+            pressed_keys: HashSet::new(),
+            script_callbacks: HashMap::new(),
+            script_dialog_callback: None,
+            script_loads: 0,
+            scroll_line_size: DEFAULT_LINE_SIZE,
+            scroll_page_size: None,
+            scroll_velocity: (0.0, 0.0),
+            scrolling: false,
+            title: None,
+            title_callback: None,
+            touches: vec![],
+            uri_scheme_urls: HashMap::new(),
+            url: None,
+            url_callback: None,
+            user_content_manager: self.user_content_manager.unwrap_or_else(UserContentManager::new),
             view: view.clone(),
-            window,
-            zoom_level: Cell::new(1.0),
+            zoom_level: Cell::new(self.zoom_level),
         }));
 
-        {
-            let state = state.clone();
+        let webview = WebView {
+            state,
+        };
+
+        let handler = {
+            let context = webview.get_context();
+            view.connect_create_context(move |view| context.gl_context(view))
+        };
+        webview.state.borrow_mut().handlers.push(handler);
+
+        let handler = {
+            let webview = webview.clone();
             view.connect_realize(move |_| {
-                Self::prepare(state.clone());
-            });
-        }
+                webview.prepare();
+            })
+        };
+        webview.state.borrow_mut().handlers.push(handler);
 
-        WebView {
-            state,
-        }
+        let handler = {
+            let webview = webview.clone();
+            view.connect_map(move |_| {
+                webview.activate();
+            })
+        };
+        webview.state.borrow_mut().handlers.push(handler);
+
+        Ok(webview)
+    }
+
+    /// Whether the view can take the keyboard focus (default: true).
+    pub fn can_focus(mut self, can_focus: bool) -> Self {
+        self.can_focus = can_focus;
+        self
+    }
+
+    /// Share the Servo instance of `context` instead of the default one.
+    pub fn context(mut self, context: &WebContext) -> Self {
+        self.context = Some(context.clone());
+        self
+    }
+
+    /// Whether the back and forward buttons of the mouse navigate in the history (default: true).
+    pub fn navigation_buttons(mut self, enabled: bool) -> Self {
+        self.navigation_buttons = enabled;
+        self
     }
 
+    /// Share the Servo instance of `webview`.
+    pub fn related_view(mut self, webview: &WebView) -> Self {
+        self.context = Some(webview.get_context());
+        self
+    }
+
+    /// Minimum size of the view (default: 200×200).
+    pub fn size_request(mut self, width: i32, height: i32) -> Self {
+        self.size_request = (width, height);
+        self
+    }
+
+    /// URL loaded when the view is realized (default: https://servo.org). Use `about:blank` to
+    /// avoid any network request.
+    pub fn url(mut self, url: &str) -> Self {
+        self.url = url.to_string();
+        self
+    }
+
+    /// Share the scripts and style sheets of `manager` instead of using a new manager.
+    pub fn user_content_manager(mut self, manager: &UserContentManager) -> Self {
+        self.user_content_manager = Some(manager.clone());
+        self
+    }
+
+    pub fn zoom(mut self, zoom_level: f32) -> Self {
+        self.zoom_level = zoom_level;
+        self
+    }
+}
+
+/// A reference to a `WebView` that does not keep it alive, held by its `WebContext`.
+pub(crate) struct WeakWebView {
+    state: Weak<RefCell<State>>,
+}
+
+impl WeakWebView {
+    pub fn upgrade(&self) -> Option<WebView> {
+        self.state.upgrade()
+            .map(|state| WebView {
+                state,
+            })
+    }
+}
+
+impl WebView {
+    /// Create a view using the default context.
+    pub fn new() -> Self {
+        WebViewBuilder::new().build()
+            .expect("default URL is valid")
+    }
+
+    /// Create a view sharing the Servo instance of `context`.
+    pub fn new_with_context(context: &WebContext) -> Self {
+        WebViewBuilder::new()
+            .context(context)
+            .build()
+            .expect("default URL is valid")
+    }
+
+    /// Make this view the one the context paints into.
     fn activate(&self) {
-        // FIXME: can we avoid calling this method everytime?
-        with_servo!(self, |browser_id, servo| {
-            let event = WindowEvent::SelectBrowser(browser_id);
-            servo.handle_events(vec![event]);
+        with_browser!(self, |browser_id| {
+            let (context, view, zoom_level) = {
+                let state = self.state.borrow();
+                (state.context.clone(), state.view.clone(), state.zoom_level.get())
+            };
+            if !context.is_active(browser_id) {
+                context.select(browser_id, &view);
+                // The zoom is global to the compositor, so restore the one of this view.
+                let mut events = vec![WindowEvent::ResetZoom];
+                if zoom_level != 1.0 {
+                    events.push(WindowEvent::Zoom(zoom_level));
+                }
+                context.handle_events(events);
+            }
         }, no_activate);
     }
 
+    /// Use the browser Servo created for a new window opened by a page.
+    pub(crate) fn adopt_browser(&self, browser_id: BrowserId, context: &WebContext) {
+        let mut state = self.state.borrow_mut();
+        state.browser_id = Some(browser_id);
+        // The browser lives in the context of the page that opened it.
+        state.context = context.clone();
+        state.pending_url = None;
+    }
+
+    /// Let Servo do an allowed navigation, or load the response of the scheme handler instead.
+    fn allow_navigation(&self, url: ServoUrl, sender: &ipc::IpcSender<bool>) {
+        match self.resolve_uri_scheme(&url) {
+            Some(data_url) => {
+                // Cancel the navigation Servo cannot do, and load the response instead.
+                sender.send(false).ok();
+                self.load_internal(data_url);
+            },
+            None => {
+                self.state.borrow_mut().loading_url = Some(url.as_str().to_string());
+                sender.send(true).ok();
+            },
+        }
+    }
+
+    /// Create the browser of the view in the engine, loading the pending URL.
+    fn attach(&self) -> Result<(), Error> {
+        let (context, view) = {
+            let state = self.state.borrow();
+            (state.context.clone(), state.view.clone())
+        };
+        let browser_id = context.attach(self, &view)?;
+        let url = self.state.borrow_mut().pending_url.take()
+            .unwrap_or_else(|| ServoUrl::parse(DEFAULT_URL).unwrap());
+        self.state.borrow_mut().loading_url = Some(url.as_str().to_string());
+        let url = self.resolve_uri_scheme(&url).unwrap_or(url);
+        self.state.borrow_mut().browser_id = Some(browser_id);
+        context.handle_events(vec![WindowEvent::NewBrowser(url, browser_id)]);
+        Ok(())
+    }
+
     pub fn back(&self) {
-        with_servo!(self, |browser_id, servo| {
+        with_browser!(self, |browser_id| {
             let event = WindowEvent::Navigation(browser_id, TraversalDirection::Back(1));
-            servo.handle_events(vec![event]);
+            self.handle_events(vec![event]);
         });
     }
 
+    /// Servo closed the browser, either because it was asked to or because the page called
+    /// `window.close()`.
+    fn browser_closed(&self) {
+        let (requested, callback) = {
+            let state = self.state.borrow();
+            (!state.closed && !state.closing, state.close_requested_callback.clone())
+        };
+        if requested {
+            if let Some(callback) = callback {
+                callback();
+            }
+        }
+        self.destroy();
+    }
+
     pub fn can_go_back(&self) -> bool {
-        let state = self.state.borrow();
-        state.window.can_go_back()
+        self.state.borrow().can_go_back
     }
 
     pub fn can_go_forward(&self) -> bool {
-        let state = self.state.borrow();
-        state.window.can_go_forward()
+        self.state.borrow().can_go_forward
     }
 
+    /// Close the page, once its `beforeunload` handlers allowed to leave it. The view is destroyed
+    /// when Servo has closed its browser, see `connect_destroyed()`.
     pub fn close(&self) {
-        // FIXME: warning.
-        // FIXME: should change the url (i.e. because it triggers page switch).
-        with_servo!(self, |browser_id, servo| {
-            let event = WindowEvent::CloseBrowser(browser_id);
-            servo.handle_events(vec![event]);
-        });
+        let browser_id = {
+            let mut state = self.state.borrow_mut();
+            if state.closed || state.closing {
+                return;
+            }
+            state.browser_id
+        };
+        match browser_id {
+            Some(browser_id) => {
+                // Servo only runs the beforeunload handlers when navigating: leave the page first and
+                // close it when the next one starts loading.
+                let generation = {
+                    let mut state = self.state.borrow_mut();
+                    state.closing = true;
+                    state.close_generation += 1;
+                    state.close_generation
+                };
+                let url = ServoUrl::parse("about:blank").expect("about:blank is a valid URL");
+                self.load_internal(url);
+                // Servo can drop the navigation: close the browser anyway if the page is not left.
+                let webview = self.clone();
+                gtk::timeout_add(CLOSE_TIMEOUT_MS, move || {
+                    let forced = {
+                        let state = webview.state.borrow();
+                        state.closing && state.close_generation == generation
+                    };
+                    if forced {
+                        webview.finish_close();
+                    }
+                    Continue(false)
+                });
+            },
+            None => self.destroy(),
+        }
+    }
+
+    /// The callback is called when the page closed itself with `window.close()`. Servo already
+    /// closed the browser, so the view is destroyed right after.
+    pub fn connect_close_requested<F: Fn() + 'static>(&self, callback: F) {
+        self.state.borrow_mut().close_requested_callback = Some(Rc::new(callback));
+    }
+
+    /// The callback is asked for a view to show a new window opened by the page, or a link
+    /// middle-clicked, and returns `None` to block it. The view must be new, and is expected to be
+    /// created with `WebViewBuilder::related_view()`: it uses the context of this view anyway.
+    ///
+    /// Servo does not give the URL of the windows opened by the pages, nor their features: the URL
+    /// of the action is only known for the middle-clicked links.
+    pub fn connect_create<F: Fn(&NavigationAction) -> Option<WebView> + 'static>(&self, callback: F) {
+        self.state.borrow_mut().create_callback = Some(Rc::new(callback));
+    }
+
+    /// The callback can return `Inhibit(true)` to set the cursor itself.
+    pub fn connect_cursor_changed<F: Fn(CursorKind) -> Inhibit + 'static>(&self, callback: F) {
+        self.state.borrow_mut().cursor_changed_callback = Some(Rc::new(callback));
+    }
+
+    /// The callback decides whether to allow the top-level navigations and the new windows. The
+    /// decision can be kept to be taken later. The loads the view does itself, e.g. to run a script
+    /// or to close the page, are not seen.
+    pub fn connect_decide_policy<F: Fn(&PolicyDecision) + 'static>(&self, callback: F) {
+        self.state.borrow_mut().decide_policy_callback = Some(Rc::new(callback));
+    }
+
+    /// The callback is called once the browser of the view is closed: the view cannot be used
+    /// anymore, and the widget can be removed.
+    pub fn connect_destroyed<F: Fn() + 'static>(&self, callback: F) {
+        self.state.borrow_mut().destroyed_callback = Some(Rc::new(callback));
+    }
+
+    /// The callback receives the position or the size asked by the page for its window.
+    pub fn connect_geometry_requested<F: Fn(GeometryRequest) + 'static>(&self, callback: F) {
+        self.state.borrow_mut().geometry_requested_callback = Some(Rc::new(callback));
+    }
+
+    pub fn connect_load_changed<F: Fn(LoadEvent) + 'static>(&self, callback: F) {
+        self.state.borrow_mut().load_changed_callback = Some(Rc::new(callback));
+    }
+
+    /// The callback receives the URL that failed to load and the error.
+    ///
+    /// Servo only reports the failures through its error pages, so this needs JavaScript to be
+    /// enabled. The error is guessed from the English message of the error page: it is
+    /// `NetError::FAILED` when the message is not recognized.
+    pub fn connect_load_failed<F: Fn(String, NetError) + 'static>(&self, callback: F) {
+        self.state.borrow_mut().load_failed_callback = Some(Rc::new(callback));
+    }
+
+    /// The callback receives the URL of the link under the pointer, or `None` when the pointer
+    /// leaves it.
+    ///
+    /// When the pointer leaves the view, the page gets `mouseout` and `mouseleave` events, but
+    /// Servo keeps the `:hover` state of the elements until the pointer comes back.
+    pub fn connect_mouse_target_changed<F: Fn(Option<String>) + 'static>(&self, callback: F) {
+        self.state.borrow_mut().mouse_target_changed_callback = Some(Rc::new(callback));
+    }
+
+    /// The callback can return `Inhibit(true)` to show its own dialog instead of the default
+    /// `MessageDialog`.
+    pub fn connect_script_dialog<F: Fn(&ScriptDialog) -> Inhibit + 'static>(&self, callback: F) {
+        self.state.borrow_mut().script_dialog_callback = Some(Rc::new(callback));
     }
 
     pub fn connect_title_changed<F: Fn(Option<String>) + 'static>(&self, callback: F) {
-        let state = self.state.borrow();
-        state.window.connect_title_changed(callback);
+        self.state.borrow_mut().title_callback = Some(Rc::new(callback));
     }
 
     pub fn connect_url_changed<F: Fn(String) + 'static>(&self, callback: F) {
-        let state = self.state.borrow();
-        state.window.connect_url_changed(callback);
+        self.state.borrow_mut().url_callback = Some(Rc::new(callback));
+    }
+
+    /// Copy the selection of the focused text field to the clipboard. Servo does not copy the text
+    /// selected elsewhere in the page.
+    pub fn copy(&self) {
+        self.send_shortcut('c', Code::KeyC);
+    }
+
+    fn create(&self, action: &NavigationAction) -> Option<WebView> {
+        let callback = self.state.borrow().create_callback.clone();
+        callback.and_then(|callback| callback(action))
+    }
+
+    /// Cut the selection of the focused text field to the clipboard.
+    pub fn cut(&self) {
+        self.send_shortcut('x', Code::KeyX);
+    }
+
+    fn decide_policy(&self, decision: PolicyDecision) {
+        let callback = self.state.borrow().decide_policy_callback.clone();
+        if let Some(callback) = callback {
+            callback(&decision);
+        }
+    }
+
+    /// Detach the view from the engine and drop its handlers and callbacks, which refer to the view.
+    fn destroy(&self) {
+        let (browser_id, context) = {
+            let mut state = self.state.borrow_mut();
+            state.closing = false;
+            state.closed = true;
+            (state.browser_id.take(), state.context.clone())
+        };
+        if let Some(browser_id) = browser_id {
+            context.detach(browser_id);
+        }
+        self.fail_scripts(|| Error::NotReady);
+        let callback = {
+            let mut state = self.state.borrow_mut();
+            for handler in state.handlers.drain(..) {
+                state.view.disconnect(handler);
+            }
+            for handler in state.im_handlers.drain(..) {
+                state.im_context.disconnect(handler);
+            }
+            state.pinch_gesture = None;
+            state.close_requested_callback = None;
+            state.create_callback = None;
+            state.cursor_changed_callback = None;
+            state.decide_policy_callback = None;
+            state.geometry_requested_callback = None;
+            state.load_changed_callback = None;
+            state.load_failed_callback = None;
+            state.message_handlers.clear();
+            state.mouse_target_changed_callback = None;
+            state.script_dialog_callback = None;
+            state.title_callback = None;
+            state.url_callback = None;
+            state.destroyed_callback.take()
+        };
+        if let Some(callback) = callback {
+            callback();
+        }
+    }
+
+    pub(crate) fn downgrade(&self) -> WeakWebView {
+        WeakWebView {
+            state: Rc::downgrade(&self.state),
+        }
+    }
+
+    /// Give the error to the callbacks of the scripts still running: they will never report back.
+    fn fail_scripts<E: Fn() -> Error>(&self, error: E) {
+        let callbacks: Vec<_> = self.state.borrow_mut().script_callbacks.drain().collect();
+        for (_, (_, callback)) in callbacks {
+            callback(Err(error()));
+        }
+    }
+
+    /// Ask Servo to close the browser of the page that was left.
+    fn finish_close(&self) {
+        {
+            let mut state = self.state.borrow_mut();
+            state.closing = false;
+            state.closed = true;
+        }
+        // The view is destroyed once Servo confirms the browser is closed.
+        with_browser!(self, |browser_id| {
+            let context = self.state.borrow().context.clone();
+            context.handle_events(vec![WindowEvent::CloseBrowser(browser_id)]);
+        }, no_activate);
     }
 
     pub fn forward(&self) {
-        with_servo!(self, |browser_id, servo| {
+        with_browser!(self, |browser_id| {
             let event = WindowEvent::Navigation(browser_id, TraversalDirection::Forward(1));
-            servo.handle_events(vec![event]);
+            self.handle_events(vec![event]);
         });
     }
 
+    fn geometry_requested(&self, request: GeometryRequest) {
+        let callback = self.state.borrow().geometry_requested_callback.clone();
+        if let Some(callback) = callback {
+            callback(request);
+        }
+    }
+
+    pub fn get_context(&self) -> WebContext {
+        self.state.borrow().context.clone()
+    }
+
+    /// Get a rough estimate of the progress of the current load, between 0.0 and 1.0.
+    pub fn get_estimated_load_progress(&self) -> f64 {
+        match self.state.borrow().load_event {
+            Some(LoadEvent::Started) => 0.1,
+            Some(LoadEvent::Committed) => 0.5,
+            Some(LoadEvent::Finished) | None => 1.0,
+        }
+    }
+
     pub fn get_title(&self) -> Option<String> {
-        let state = self.state.borrow();
-        state.window.get_title()
+        self.state.borrow().title.clone()
     }
 
     pub fn get_url(&self) -> Option<String> {
-        let state = self.state.borrow();
-        state.window.get_url()
+        self.state.borrow().url.clone()
+    }
+
+    pub fn get_user_content_manager(&self) -> UserContentManager {
+        self.state.borrow().user_content_manager.clone()
     }
 
     pub fn get_zoom(&self) -> f32 {
@@ -245,157 +778,788 @@ impl WebView {
         state.zoom_level.get()
     }
 
-    pub fn load(&self, url: &str) {
-        with_servo!(self, |browser_id, servo| {
-            match ServoUrl::parse(url) {
-                Ok(url) => {
-                    let event = WindowEvent::LoadUrl(browser_id, url);
-                    servo.handle_events(vec![event]);
-                },
-                // TODO: return an error.
-                Err(error) => println!("Error: {}", error),
+    fn handle_bridge_msg(&self, message: Message) {
+        match message {
+            Message::HandlerMessage { token, id, name, message } => {
+                let handler = {
+                    let state = self.state.borrow();
+                    if state.message_token.as_ref() != Some(&token) {
+                        // Forged by a frame, or sent by the previous document.
+                        return;
+                    }
+                    let url = state.url.clone().unwrap_or_default();
+                    state.message_handlers.get(&name)
+                        .filter(|&&(ref allow_list, _)| user_content::matches(allow_list, &url))
+                        .map(|&(_, ref handler)| handler.clone())
+                };
+                let reply =
+                    match handler {
+                        Some(handler) => bridge::reply(id, handler(message), None),
+                        None => bridge::reply(id, None, Some(&format!("no message handler named {}", name))),
+                    };
+                self.run_in_page(&reply);
+            },
+            Message::LoadFailed { nonce, url, reason } => {
+                if !self.get_context().take_error_page_nonce(&nonce) {
+                    // Forged by a page.
+                    return;
+                }
+                let top_level = {
+                    let state = self.state.borrow();
+                    state.url.as_ref() == Some(&url) || state.loading_url.as_ref() == Some(&url)
+                };
+                if !top_level {
+                    // Reported by the error page of a frame.
+                    return;
+                }
+                let callback = self.state.borrow().load_failed_callback.clone();
+                if let Some(callback) = callback {
+                    callback(url, convert::net_error(&reason));
+                }
+            },
+            Message::ScriptError { id, token, name, message } => {
+                if let Some(callback) = self.take_script_callback(id, &token) {
+                    callback(Err(Error::JavascriptException { name, message }));
+                }
+            },
+            Message::ScriptResult { id, token, result } => {
+                if let Some(callback) = self.take_script_callback(id, &token) {
+                    callback(Ok(result));
+                }
+            },
+        }
+    }
+
+    pub(crate) fn handle_embedder_msg(&self, message: EmbedderMsg) {
+        let closing = {
+            let state = self.state.borrow();
+            state.closed || state.closing
+        };
+        if closing {
+            match message {
+                // The page being left must not change the view anymore.
+                EmbedderMsg::ChangePageTitle(_) | EmbedderMsg::HeadParsed | EmbedderMsg::HistoryChanged(..)
+                    | EmbedderMsg::LoadComplete | EmbedderMsg::SetCursor(_) | EmbedderMsg::Status(_) => return,
+                _ => (),
+            }
+        }
+        match message {
+            EmbedderMsg::ChangePageTitle(title) => self.title_changed(title),
+            EmbedderMsg::CloseBrowser => self.browser_closed(),
+            EmbedderMsg::HeadParsed => {
+                self.load_committed();
+                self.inject_message_bridge();
+                self.inject_user_content(InjectionTime::DocumentStart);
+            },
+            EmbedderMsg::HistoryChanged(entries, current) => {
+                self.history_changed(entries, current);
+                self.load_committed();
+            },
+            EmbedderMsg::LoadComplete => {
+                self.load_changed(LoadEvent::Finished);
+                self.inject_user_content(InjectionTime::DocumentEnd);
+            },
+            EmbedderMsg::LoadStart => {
+                if !self.take_script_load() {
+                    self.state.borrow_mut().message_token = None;
+                    self.fail_scripts(|| Error::ScriptAborted);
+                    self.load_changed(LoadEvent::Started);
+                }
+            },
+            EmbedderMsg::MoveTo(point) => self.geometry_requested(GeometryRequest::Move {
+                x: point.x as i32,
+                y: point.y as i32,
+            }),
+            EmbedderMsg::ResizeTo(size) => self.geometry_requested(GeometryRequest::Resize {
+                width: size.width as i32,
+                height: size.height as i32,
+            }),
+            EmbedderMsg::SetClipboardContents(text) => {
+                // PRIMARY is left alone: Servo does not tell when the selection changes.
+                Clipboard::get(&SELECTION_CLIPBOARD).set_text(&text);
+            },
+            EmbedderMsg::SetCursor(cursor) => self.set_cursor(cursor),
+            EmbedderMsg::Status(link) => self.mouse_target_changed(link),
+            // The messages below expect an answer: the page would hang if we dropped the sender.
+            EmbedderMsg::Alert(message, sender) => {
+                match bridge::parse(&message) {
+                    Some(message) => {
+                        sender.send(()).ok();
+                        self.handle_bridge_msg(message);
+                    },
+                    None => {
+                        let dialog = ScriptDialog::new(ScriptDialogType::Alert, message, move |_| {
+                            sender.send(()).ok();
+                        });
+                        self.script_dialog(dialog);
+                    },
+                }
+            },
+            EmbedderMsg::AllowNavigation(url, sender) => {
+                if self.take_internal_load(&url) {
+                    // The page does not navigate: the view loads the URL itself.
+                    sender.send(true).ok();
+                    return;
+                }
+                let webview = self.downgrade();
+                let action = self.navigation_action(Some(url.as_str().to_string()));
+                let decision = PolicyDecision::new(PolicyDecisionType::NavigationAction, action, move |decision| {
+                    match webview.upgrade() {
+                        Some(ref webview) if decision == Decision::Use =>
+                            webview.allow_navigation(url.clone(), &sender),
+                        _ => {
+                            sender.send(false).ok();
+                        },
+                    }
+                });
+                self.decide_policy(decision);
+            },
+            EmbedderMsg::AllowOpeningBrowser(sender) => {
+                let webview = self.downgrade();
+                let action = self.navigation_action(None);
+                let decision = PolicyDecision::new(PolicyDecisionType::NewWindowAction, action.clone(),
+                    move |decision| {
+                        let new_view = webview.upgrade()
+                            .filter(|_| decision == Decision::Use)
+                            .and_then(|webview| webview.create(&action).map(|new_view| (webview, new_view)));
+                        match new_view {
+                            Some((webview, new_view)) => {
+                                // Servo creates the browser itself: the view gets it from the context.
+                                webview.get_context().add_pending_view(&new_view);
+                                sender.send(true).ok();
+                            },
+                            None => {
+                                sender.send(false).ok();
+                            },
+                        }
+                    });
+                self.decide_policy(decision);
+            },
+            EmbedderMsg::AllowUnload(sender) => {
+                // The user decides when to leave the page: do not force the close meanwhile.
+                self.state.borrow_mut().close_generation += 1;
+                let webview = self.downgrade();
+                let dialog = ScriptDialog::new(ScriptDialogType::BeforeUnloadConfirm, String::new(), move |leave| {
+                    sender.send(leave).ok();
+                    if let Some(webview) = webview.upgrade() {
+                        if !leave {
+                            webview.state.borrow_mut().closing = false;
+                        }
+                    }
+                });
+                self.script_dialog(dialog);
+            },
+            EmbedderMsg::GetClipboardContents(sender) => {
+                let selection =
+                    if self.state.borrow().paste_primary {
+                        SELECTION_PRIMARY
+                    }
+                    else {
+                        SELECTION_CLIPBOARD
+                    };
+                self.state.borrow_mut().paste_primary = false;
+                Clipboard::get(&selection).request_text(move |_, text| {
+                    sender.send(text.unwrap_or_default().to_string()).ok();
+                });
+            },
+            EmbedderMsg::GetSelectedBluetoothDevice(_devices, sender) => {
+                sender.send(None).ok();
+            },
+            EmbedderMsg::SelectFiles(_patterns, _multiple, sender) => {
+                sender.send(None).ok();
+            },
+            EmbedderMsg::HideIME => self.hide_ime(),
+            EmbedderMsg::ShowIME(_input_type) => self.show_ime(),
+            _ => (),
+        }
+    }
+
+    /// Send events coming from this view to the context, selecting this view first if needed.
+    fn handle_events(&self, events: Vec<WindowEvent>) {
+        self.activate();
+        let context = self.state.borrow().context.clone();
+        context.handle_events(events);
+    }
+
+    fn hide_ime(&self) {
+        let state = self.state.borrow();
+        state.im_context.reset();
+        state.im_context.focus_out();
+    }
+
+    fn history_changed(&self, entries: Vec<ServoUrl>, current: usize) {
+        let (url, callback) = {
+            let mut state = self.state.borrow_mut();
+            state.can_go_back = !entries.is_empty() && current > 0;
+            state.can_go_forward = !entries.is_empty() && current < entries.len() - 1;
+            let url = match entries.get(current) {
+                Some(url) => url.as_str().to_string(),
+                None => return,
+            };
+            // The data: URLs can be big: only keep the ones that can still be shown.
+            let hashes: HashSet<u64> = entries.iter().map(|entry| url_hash(entry.as_str())).collect();
+            state.uri_scheme_urls.retain(|hash, _| hashes.contains(hash));
+            // Show the URL the user asked for instead of the response of the handler.
+            let url = state.uri_scheme_urls.get(&url_hash(&url)).cloned().unwrap_or(url);
+            state.url = Some(url.clone());
+            (url, state.url_callback.clone())
+        };
+        if let Some(callback) = callback {
+            callback(url);
+        }
+    }
+
+    /// Get the URL of the link under the pointer.
+    pub fn hovered_link(&self) -> Option<String> {
+        self.state.borrow().hovered_link.clone()
+    }
+
+    fn im_commit(&self, text: &str) {
+        // The text comes from the key being filtered, or directly from the input method.
+        let (key_event, direct) = {
+            let mut state = self.state.borrow_mut();
+            match state.filtered_key.take() {
+                Some(event) => (event, false),
+                None => (KeyboardEvent::default(), true),
+            }
+        };
+        let down = KeyboardEvent {
+            state: KeyState::Down,
+            key: Key::Character(text.to_string()),
+            is_composing: false,
+            ..key_event
+        };
+        let mut events = vec![];
+        if direct {
+            let up = KeyboardEvent {
+                state: KeyState::Up,
+                ..down.clone()
+            };
+            events.push(WindowEvent::Keyboard(down));
+            events.push(WindowEvent::Keyboard(up));
+        }
+        else {
+            events.push(WindowEvent::Keyboard(down));
+        }
+        self.handle_events(events);
+    }
+
+    /// Define `window.servoGtk` in the document if a message handler allows its URL.
+    fn inject_message_bridge(&self) {
+        let token = {
+            let mut state = self.state.borrow_mut();
+            let url = state.url.clone().unwrap_or_default();
+            let allowed = state.message_handlers.values()
+                .any(|&(ref allow_list, _)| user_content::matches(allow_list, &url));
+            if !allowed {
+                return;
+            }
+            state.message_token.get_or_insert_with(bridge::nonce).clone()
+        };
+        self.run_in_page(&bridge::message_script(&token));
+    }
+
+    fn inject_user_content(&self, injection_time: InjectionTime) {
+        let script = self.state.borrow().user_content_manager.injection_script(injection_time);
+        if let Some(script) = script {
+            self.run_in_page(&script);
+        }
+    }
+
+    pub fn is_loading(&self) -> bool {
+        match self.state.borrow().load_event {
+            Some(LoadEvent::Started) | Some(LoadEvent::Committed) => true,
+            Some(LoadEvent::Finished) | None => false,
+        }
+    }
+
+    fn key_event(&self, event: &EventKey, state: KeyState) {
+        // A middle-click paste the page did not ask the text for must not change the next Ctrl+V.
+        self.state.borrow_mut().paste_primary = false;
+        let hardware_keycode = event.get_hardware_keycode();
+        let (key, code, location) = convert::key(event.get_keyval());
+        // Prefer the physical key reported by the hardware to the one guessed from the keyval.
+        let code = convert::code(hardware_keycode).unwrap_or(code);
+        let (repeat, composing, im_context) = {
+            let mut inner_state = self.state.borrow_mut();
+            let repeat =
+                match state {
+                    KeyState::Down => !inner_state.pressed_keys.insert(hardware_keycode),
+                    KeyState::Up => {
+                        inner_state.pressed_keys.remove(&hardware_keycode);
+                        false
+                    },
+                };
+            (repeat, inner_state.composing, inner_state.im_context.clone())
+        };
+        let keyboard_event = KeyboardEvent {
+            state,
+            key,
+            code,
+            location,
+            modifiers: convert::modifiers(event.get_state()),
+            repeat,
+            is_composing: composing,
+        };
+
+        // Give the input method a chance to handle the key: the text it produces comes back through
+        // the commit signal, emitted from filter_keypress().
+        self.state.borrow_mut().filtered_key = Some(keyboard_event);
+        let filtered = im_context.filter_keypress(event);
+        let keyboard_event = self.state.borrow_mut().filtered_key.take();
+        if let Some(mut keyboard_event) = keyboard_event {
+            if filtered {
+                // A dead key, or a key used by the input method to compose the text.
+                if self.state.borrow().composing {
+                    keyboard_event.key = Key::Process;
+                }
+                keyboard_event.is_composing = true;
+            }
+            self.handle_events(vec![WindowEvent::Keyboard(keyboard_event)]);
+        }
+    }
+
+    /// Continue the scroll with a decreasing velocity until it stops, or another scroll starts.
+    fn kinetic_scroll(&self, velocity: (f32, f32)) {
+        let generation = self.state.borrow().kinetic_generation;
+        let velocity = Cell::new(velocity);
+        let webview = self.clone();
+        gtk::timeout_add(KINETIC_FRAME_MS, move || {
+            if webview.state.borrow().kinetic_generation != generation {
+                return Continue(false);
+            }
+            let (dx, dy) = velocity.get();
+            if dx.abs() < KINETIC_MIN_DELTA && dy.abs() < KINETIC_MIN_DELTA {
+                webview.send_scroll(0.0, 0.0, TouchEventType::Up);
+                return Continue(false);
+            }
+            webview.send_scroll(dx, dy, TouchEventType::Move);
+            velocity.set((dx * KINETIC_FRICTION, dy * KINETIC_FRICTION));
+            Continue(true)
+        });
+    }
+
+    /// Load `url`, or load it once the view is realized if it is not yet.
+    ///
+    /// The engine cannot start without its resources: until they are found, the view stays
+    /// detached and this returns `Error::MissingResource`.
+    pub fn load(&self, url: &str) -> Result<(), Error> {
+        let url = ServoUrl::parse(url)
+            .map_err(|error| Error::InvalidUrl(format!("{}: {}", url, error)))?;
+        let (browser_id, closed, context, prepared) = {
+            let state = self.state.borrow();
+            (state.browser_id, state.closed || state.closing, state.context.clone(), state.prepared)
+        };
+        if context.is_shut_down() {
+            return Err(Error::EngineShutdown);
+        }
+        if closed {
+            return Err(Error::NotReady);
+        }
+        match browser_id {
+            Some(browser_id) => {
+                let url = self.resolve_uri_scheme(&url).unwrap_or(url);
+                self.handle_events(vec![WindowEvent::LoadUrl(browser_id, url)]);
+            },
+            None => {
+                context.check_resources()?;
+                // Only the last load matters, so it replaces any previous one.
+                self.state.borrow_mut().pending_url = Some(url);
+                // The view was realized while the resources were missing.
+                if prepared {
+                    self.attach()?;
+                    self.activate();
+                }
+            },
+        }
+        Ok(())
+    }
+
+    fn load_changed(&self, event: LoadEvent) {
+        let (closed, closing) = {
+            let state = self.state.borrow();
+            (state.closed, state.closing)
+        };
+        if closing && event == LoadEvent::Started {
+            // The page was left: it can now be closed.
+            self.finish_close();
+            return;
+        }
+        if closed {
+            return;
+        }
+        let callback = {
+            let mut state = self.state.borrow_mut();
+            state.load_event = Some(event);
+            state.load_changed_callback.clone()
+        };
+        if let Some(callback) = callback {
+            callback(event);
+        }
+    }
+
+    /// Servo does not tell when a load is committed, so consider it is when the history or the
+    /// document changes during the load.
+    fn load_committed(&self) {
+        if self.state.borrow().load_event == Some(LoadEvent::Started) {
+            self.load_changed(LoadEvent::Committed);
+        }
+    }
+
+    /// Load `url` for the view itself, e.g. to run a script or to close the page: this does not show
+    /// the view, and the callback of connect_decide_policy() does not see the navigation.
+    fn load_internal(&self, url: ServoUrl) {
+        with_browser!(self, |browser_id| {
+            let context = {
+                let mut state = self.state.borrow_mut();
+                if state.internal_loads.len() == MAX_INTERNAL_LOADS {
+                    state.internal_loads.pop_front();
+                }
+                state.internal_loads.push_back(url_hash(url.as_str()));
+                state.context.clone()
+            };
+            context.handle_events(vec![WindowEvent::LoadUrl(browser_id, url)]);
+        }, no_activate);
+    }
+
+    fn mouse_target_changed(&self, link: Option<String>) {
+        let callback = {
+            let mut state = self.state.borrow_mut();
+            if state.hovered_link == link {
+                return;
+            }
+            state.hovered_link = link.clone();
+            state.mouse_target_changed_callback.clone()
+        };
+        if let Some(callback) = callback {
+            callback(link);
+        }
+    }
+
+    /// Servo does not tell what triggered a navigation, so guess from the last input.
+    fn navigation_action(&self, url: Option<String>) -> NavigationAction {
+        match self.state.borrow().last_input {
+            Some((time, button, modifiers)) if time.elapsed() < Duration::from_millis(USER_GESTURE_DELAY_MS) =>
+                NavigationAction::new(url, true, button, modifiers),
+            _ => NavigationAction::new(url, false, None, ModifierType::empty()),
+        }
+    }
+
+    /// Open `url` in a new view, as for a new window.
+    fn open_link(&self, url: String) {
+        let webview = self.downgrade();
+        let action = self.navigation_action(Some(url.clone()));
+        let decision = PolicyDecision::new(PolicyDecisionType::NewWindowAction, action.clone(), move |decision| {
+            let webview =
+                match webview.upgrade() {
+                    Some(webview) if decision == Decision::Use => webview,
+                    _ => return,
+                };
+            if let Some(new_view) = webview.create(&action) {
+                if let Err(error) = new_view.load(&url) {
+                    webview.get_context().report_error(error);
+                }
             }
         });
+        self.decide_policy(decision);
     }
 
-    fn prepare(state: Rc<RefCell<State>>) {
-        state.borrow().view.make_current();
+    /// Paste the clipboard in the focused text field.
+    pub fn paste(&self) {
+        self.send_shortcut('v', Code::KeyV);
+    }
 
-        resources::set(Box::new(ResourceReader));
+    /// Paste the PRIMARY selection, as done by a middle-click.
+    fn paste_primary(&self) {
+        self.state.borrow_mut().paste_primary = true;
+        self.paste();
+    }
 
-        let servo = Rc::new(RefCell::new(servo::Servo::new(state.borrow().window.clone())));
+    fn prepare(&self) {
+        let state = &self.state;
+        if state.borrow().prepared {
+            return;
+        }
+        state.borrow_mut().prepared = true;
 
         {
-            let servo = servo.clone();
-            state.borrow_mut().rx.connect_recv(move |()| {
-                servo.borrow_mut().handle_events(vec![]);
-                Continue(true)
+            let webview = self.clone();
+            let handler = state.borrow().view.connect_key_press_event(move |_, event| {
+                webview.state.borrow_mut().last_input = Some((Instant::now(), None, event.get_state()));
+                webview.key_event(event, KeyState::Down);
+                Inhibit(false)
             });
+            state.borrow_mut().handlers.push(handler);
         }
 
         {
-            let servo = servo.clone();
-            state.borrow().view.connect_key_press_event(move |_, event| {
-                let (char, key) = convert::key(event.get_keyval());
-                if let Some(key) = key {
-                    let modifiers = convert::modifiers(event.get_state());
-                    let mut event = KeyboardEvent::default();
-                    event.key = key;
-                    event.modifiers = modifiers;
-                    let event = WindowEvent::Keyboard(event);
-                    servo.borrow_mut().handle_events(vec![event]);
-                }
+            let webview = self.clone();
+            let handler = state.borrow().view.connect_key_release_event(move |_, event| {
+                webview.key_event(event, KeyState::Up);
                 Inhibit(false)
             });
+            state.borrow_mut().handlers.push(handler);
         }
 
-        /*{
-            let servo = servo.clone();
-            state.borrow().view.connect_key_release_event(move |_, event| {
-                let (char, key) = convert::key(event.get_keyval());
-                if let Some(key) = key {
-                    let modifiers = convert::modifiers(event.get_state());
-                    let event = WindowEvent::KeyEvent(char, key, KeyState::Released, modifiers);
-                    servo.borrow_mut().handle_events(vec![event]);
-                }
+        {
+            let webview = self.clone();
+            let handler = state.borrow().view.connect_focus_out_event(move |_, _| {
+                // The releases of the keys still down will go to another widget.
+                webview.state.borrow_mut().pressed_keys.clear();
+                webview.state.borrow().im_context.focus_out();
+                Inhibit(false)
+            });
+            state.borrow_mut().handlers.push(handler);
+        }
+
+        {
+            let webview = self.clone();
+            let handler = state.borrow().view.connect_focus_in_event(move |_, _| {
+                webview.state.borrow().im_context.focus_in();
                 Inhibit(false)
             });
-        }*/
+            state.borrow_mut().handlers.push(handler);
+        }
+
+        {
+            let im_context = state.borrow().im_context.clone();
+            im_context.set_client_window(state.borrow().view.get_window().as_ref());
+
+            let webview = self.clone();
+            let handler = im_context.connect_commit(move |_, text| {
+                webview.im_commit(text);
+            });
+            state.borrow_mut().im_handlers.push(handler);
+
+            let webview = self.clone();
+            let handler = im_context.connect_preedit_start(move |_| {
+                webview.state.borrow_mut().composing = true;
+            });
+            state.borrow_mut().im_handlers.push(handler);
+
+            let webview = self.clone();
+            let handler = im_context.connect_preedit_end(move |_| {
+                webview.state.borrow_mut().composing = false;
+            });
+            state.borrow_mut().im_handlers.push(handler);
+        }
 
         {
-            let servo = servo.clone();
+            let webview = self.clone();
             let view = state.borrow().view.clone();
-            state.borrow().view.connect_button_press_event(move |_, event| {
-                view.grab_focus();
-                let (x, y) = event.get_position();
-                let event = WindowEvent::MouseWindowEventClass(MouseWindowEvent::MouseDown(
-                        convert::mouse_button(event.get_button()), TypedPoint2D::new(x as f32, y as f32)));
-                servo.borrow_mut().handle_events(vec![event]);
+            let handler = state.borrow().view.connect_button_press_event(move |_, event| {
+                if is_touch_emulated(event) {
+                    return Inhibit(false);
+                }
+                if view.get_can_focus() {
+                    view.grab_focus();
+                }
+                {
+                    let mut state = webview.state.borrow_mut();
+                    state.last_input = Some((Instant::now(), Some(event.get_button()), event.get_state()));
+                    let (x, y) = event.get_position();
+                    state.last_click = Pos::new(x, y);
+                }
+                let navigation_buttons = webview.state.borrow().navigation_buttons;
+                match event.get_button() {
+                    BUTTON_BACK if navigation_buttons => webview.back(),
+                    BUTTON_FORWARD if navigation_buttons => webview.forward(),
+                    button => {
+                        if let Some(button) = convert::mouse_button(button) {
+                            let (x, y) = event.get_position();
+                            let event = WindowEvent::MouseWindowEventClass(MouseWindowEvent::MouseDown(
+                                    button, TypedPoint2D::new(x as f32, y as f32)));
+                            webview.handle_events(vec![event]);
+                        }
+                    },
+                }
                 Inhibit(false)
             });
+            state.borrow_mut().handlers.push(handler);
         }
 
         {
-            let servo = servo.clone();
-            state.borrow().view.connect_button_release_event(move |_, event| {
+            let webview = self.clone();
+            let handler = state.borrow().view.connect_button_release_event(move |_, event| {
+                if is_touch_emulated(event) {
+                    return Inhibit(false);
+                }
                 let (x, y) = event.get_position();
-                let button = convert::mouse_button(event.get_button());
+                let button =
+                    match convert::mouse_button(event.get_button()) {
+                        Some(button) => button,
+                        None => return Inhibit(false),
+                    };
                 let event = WindowEvent::MouseWindowEventClass(MouseWindowEvent::MouseUp(
                         button, TypedPoint2D::new(x as f32, y as f32)));
-                servo.borrow_mut().handle_events(vec![event]);
+                webview.handle_events(vec![event]);
                 let event = WindowEvent::MouseWindowEventClass(MouseWindowEvent::Click(
                         button, TypedPoint2D::new(x as f32, y as f32)));
-                servo.borrow_mut().handle_events(vec![event]); // TODO: check if it is the right place to trigger this event.
+                webview.handle_events(vec![event]); // TODO: check if it is the right place to trigger this event.
+                if let MouseButton::Middle = button {
+                    let link = webview.hovered_link();
+                    match link {
+                        Some(link) => webview.open_link(link),
+                        None => webview.paste_primary(),
+                    }
+                }
                 Inhibit(false)
             });
+            state.borrow_mut().handlers.push(handler);
         }
 
         {
             let inner_state = state.clone();
-            let servo = servo.clone();
-            state.borrow().view.connect_motion_notify_event(move |_, event| {
+            let webview = self.clone();
+            let handler = state.borrow().view.connect_motion_notify_event(move |_, event| {
+                if is_touch_emulated(event) {
+                    return Inhibit(false);
+                }
                 let (x, y) = event.get_position();
-                let pointer = &mut inner_state.borrow_mut().pointer;
-                pointer.x = x;
-                pointer.y = y;
+                {
+                    let pointer = &mut inner_state.borrow_mut().pointer;
+                    pointer.x = x;
+                    pointer.y = y;
+                }
                 let event = WindowEvent::MouseWindowMoveEventClass(TypedPoint2D::new(x as f32, y as f32));
-                servo.borrow_mut().handle_events(vec![event]);
+                webview.handle_events(vec![event]);
                 Inhibit(false)
             });
+            state.borrow_mut().handlers.push(handler);
         }
 
         {
-            let servo = servo.clone();
-            state.borrow().view.connect_resize(move |_, _, _| {
-                servo.borrow_mut().handle_events(vec![WindowEvent::Resize]);
+            let webview = self.clone();
+            let handler = state.borrow().view.connect_enter_notify_event(move |_, event| {
+                let (x, y) = event.get_position();
+                {
+                    let pointer = &mut webview.state.borrow_mut().pointer;
+                    pointer.x = x;
+                    pointer.y = y;
+                }
+                let event = WindowEvent::MouseWindowMoveEventClass(TypedPoint2D::new(x as f32, y as f32));
+                webview.handle_events(vec![event]);
+                Inhibit(false)
             });
+            state.borrow_mut().handlers.push(handler);
         }
 
         {
-            let inner_state = state.clone();
-            let servo = servo.clone();
-            state.borrow().view.connect_scroll_event(move |_, event| {
-                let state = event.get_state();
-                if !state.contains(ModifierType::CONTROL_MASK) {
-                    let phase = match event.get_direction() {
-                        ScrollDirection::Down => TouchEventType::Down,
-                        ScrollDirection::Up => TouchEventType::Up,
-                        ScrollDirection::Left => TouchEventType::Cancel, // FIXME
-                        ScrollDirection::Right => TouchEventType::Cancel, // FIXME
-                        ScrollDirection::Smooth | _ => TouchEventType::Cancel, // FIXME
-                    };
-                    let dx = 0.0;
-                    let dy =
-                        match phase {
-                            TouchEventType::Up => 1.0,
-                            TouchEventType::Down => -1.0,
-                            _ => 0.0,
-                        };
-                    let dy = dy * 38.0;
-                    let pointer = {
-                        let pointer = &inner_state.borrow().pointer;
-                        TypedPoint2D::new(pointer.x as i32, pointer.y as i32)
-                    };
-                    let scroll_location = servo::webrender_api::ScrollLocation::Delta(TypedVector2D::new(dx as f32, dy as f32));
-                    let event = WindowEvent::Scroll(scroll_location, pointer, phase);
-                    servo.borrow_mut().handle_events(vec![event]);
+            let webview = self.clone();
+            let handler = state.borrow().view.connect_leave_notify_event(move |_, _| {
+                // Servo has no event for the pointer leaving the window, and the compositor drops
+                // the moves that hit nothing: the page gets the events from a script instead.
+                webview.run_in_page(bridge::POINTER_LEAVE_SCRIPT);
+                webview.mouse_target_changed(None);
+                Inhibit(false)
+            });
+            state.borrow_mut().handlers.push(handler);
+        }
+
+        {
+            let webview = self.clone();
+            let handler = state.borrow().view.connect_resize(move |_, _, _| {
+                let (browser_id, context) = {
+                    let state = webview.state.borrow();
+                    (state.browser_id, state.context.clone())
+                };
+                // Only the view being painted into decides the size of the viewport.
+                if browser_id.map(|browser_id| context.is_active(browser_id)).unwrap_or(false) {
+                    context.handle_events(vec![WindowEvent::Resize]);
+                }
+            });
+            state.borrow_mut().handlers.push(handler);
+        }
+
+        {
+            let webview = self.clone();
+            let handler = state.borrow().view.connect_touch_event(move |_, event| {
+                webview.touch_event(event);
+                Inhibit(false)
+            });
+            state.borrow_mut().handlers.push(handler);
+        }
+
+        {
+            let gesture = GestureZoom::new(&state.borrow().view);
+            let webview = self.clone();
+            gesture.connect_begin(move |_, _| {
+                // The touch points now belong to the gesture: Servo must forget them.
+                let touches = {
+                    let mut state = webview.state.borrow_mut();
+                    state.pinch_scale = 1.0;
+                    state.pinching = true;
+                    mem::replace(&mut state.touches, vec![])
+                };
+                let events = touches.into_iter()
+                    .map(|(_, touch_id, pos)| {
+                        let point = TypedPoint2D::new(pos.x as f32, pos.y as f32);
+                        WindowEvent::Touch(TouchEventType::Cancel, touch_id, point)
+                    })
+                    .collect();
+                webview.handle_events(events);
+            });
+            let webview = self.clone();
+            gesture.connect_end(move |_, _| {
+                webview.state.borrow_mut().pinching = false;
+            });
+            let webview = self.clone();
+            gesture.connect_scale_changed(move |_, scale| {
+                // Servo wants the change since the last event, GTK gives it since the beginning.
+                let magnification = {
+                    let mut state = webview.state.borrow_mut();
+                    let magnification = scale / state.pinch_scale;
+                    state.pinch_scale = scale;
+                    magnification
+                };
+                webview.handle_events(vec![WindowEvent::PinchZoom(magnification as f32)]);
+            });
+            // The widget does not keep a reference to its gestures.
+            state.borrow_mut().pinch_gesture = Some(gesture);
+        }
+
+        {
+            let webview = self.clone();
+            let handler = state.borrow().view.connect_scroll_event(move |_, event| {
+                if !event.get_state().contains(ModifierType::CONTROL_MASK) {
+                    webview.scroll_event(event);
                 }
                 Inhibit(false)
             });
+            state.borrow_mut().handlers.push(handler);
+        }
+
+        // The browser of a window opened by a page already exists.
+        if state.borrow().browser_id.is_none() {
+            // When the resources are missing, the view stays detached and load() reports the error.
+            self.attach().ok();
         }
+        self.activate();
+    }
 
-        let url = ServoUrl::parse("https://servo.org").unwrap();
-        let browser_id = BrowserId::new();
-        servo.borrow_mut().handle_events(vec![WindowEvent::NewBrowser(url, browser_id)]);
-        servo.borrow_mut().handle_events(vec![WindowEvent::SelectBrowser(browser_id)]);
-        state.borrow_mut().browser_id = Some(browser_id);
-        state.borrow_mut().servo = Some(servo);
+    /// Let the pages send messages to `handler` with `window.servoGtk.postMessage(name, message)`,
+    /// which returns a promise of the reply. The messages are JSON values, and are delivered on the
+    /// GTK main thread.
+    ///
+    /// Only the top-level documents whose URL matches one of the patterns of `allow_list` can use
+    /// the handler, e.g. `app://*/*`: the patterns are the ones of `UserContent`. Do not give a
+    /// pattern like `https://*/*` unless the handler can be called by any site the user navigates to.
+    ///
+    /// `window.servoGtk` is only defined once the head of the document is parsed, so the scripts
+    /// must not use it before the `DOMContentLoaded` event.
+    pub fn register_message_handler<F>(&self, name: &str, allow_list: &[&str], handler: F)
+        where F: Fn(Value) -> Option<Value> + 'static,
+    {
+        let allow_list = allow_list.iter().map(|pattern| pattern.to_string()).collect();
+        self.state.borrow_mut().message_handlers.insert(name.to_string(), (allow_list, Rc::new(handler)));
+        // The document already loaded did not get the bridge.
+        self.inject_message_bridge();
     }
 
     pub fn reload(&self) {
-        with_servo!(self, |browser_id, servo| {
+        with_browser!(self, |browser_id| {
             let event = WindowEvent::Reload(browser_id);
-            servo.handle_events(vec![event]);
+            self.handle_events(vec![event]);
         });
     }
 
@@ -404,19 +1568,339 @@ impl WebView {
             let state = self.state.borrow();
             state.zoom_level.set(1.0);
         }
-        with_servo!(self, |_browser_id, servo| {
-            servo.handle_events(vec![WindowEvent::ResetZoom]);
+        with_browser!(self, |_browser_id| {
+            self.handle_events(vec![WindowEvent::ResetZoom]);
+        });
+    }
+
+    /// Get the response of the handler of the scheme of `url` as a `data:` URL, if there is one.
+    fn resolve_uri_scheme(&self, url: &ServoUrl) -> Option<ServoUrl> {
+        let context = self.state.borrow().context.clone();
+        let data_url = context.resolve_uri_scheme(url)?;
+        self.state.borrow_mut().uri_scheme_urls.insert(url_hash(data_url.as_str()), url.as_str().to_string());
+        Some(data_url)
+    }
+
+    /// Run `script` in the top-level document from a `javascript:` URL.
+    fn run_in_page(&self, script: &str) {
+        let (attached, closing) = {
+            let state = self.state.borrow();
+            (state.browser_id.is_some(), state.closing)
+        };
+        if attached && !closing {
+            let url = ServoUrl::parse(&bridge::javascript_url(script))
+                .expect("javascript: URLs are valid");
+            self.state.borrow_mut().script_loads += 1;
+            self.load_internal(url);
+        }
+    }
+
+    /// Run `script` in the top-level document and give its result, converted to JSON, to
+    /// `callback`: `undefined` and the values JSON cannot represent become `null`. If the script
+    /// throws, the callback gets an `Error::JavascriptException`.
+    ///
+    /// Servo does not give the embedder access to its WebDriver script evaluation, so the script
+    /// runs from a `javascript:` URL and reports back with `alert()`. The callback gets
+    /// `Error::JavascriptDisabled` if JavaScript is disabled, and `Error::ScriptAborted` if the page
+    /// is left before the script reports back.
+    ///
+    /// The report carries a random token, so other pages cannot forge it. The script runs with the
+    /// globals of the page though: a hostile page overriding `alert()`, `eval()` or `JSON` can
+    /// intercept it.
+    pub fn run_javascript<F: Fn(Result<Value, Error>) + 'static>(&self, script: &str, callback: F) {
+        let (browser_id, closed, context) = {
+            let state = self.state.borrow();
+            (state.browser_id, state.closed || state.closing, state.context.clone())
+        };
+        if context.is_shut_down() {
+            callback(Err(Error::EngineShutdown));
+            return;
+        }
+        if browser_id.is_none() || closed {
+            callback(Err(Error::NotReady));
+            return;
+        }
+        if !context.get_settings().get_enable_javascript() {
+            callback(Err(Error::JavascriptDisabled));
+            return;
+        }
+        let token = bridge::nonce();
+        let id = {
+            let mut state = self.state.borrow_mut();
+            let id = state.next_script_id;
+            state.next_script_id += 1;
+            state.script_callbacks.insert(id, (token.clone(), Rc::new(callback)));
+            id
+        };
+        self.run_in_page(&bridge::run_script(id, &token, script));
+    }
+
+    fn script_dialog(&self, dialog: ScriptDialog) {
+        let callback = self.state.borrow().script_dialog_callback.clone();
+        if let Some(callback) = callback {
+            if let Inhibit(true) = callback(&dialog) {
+                return;
+            }
+        }
+        let parent = self.state.borrow().view.get_toplevel()
+            .filter(WidgetExt::is_toplevel)
+            .and_then(|toplevel| toplevel.downcast::<Window>().ok());
+        dialog.show(parent.as_ref());
+    }
+
+    fn scroll_event(&self, event: &EventScroll) {
+        let (line_size, page_size) = {
+            let state = self.state.borrow();
+            let page_size = state.scroll_page_size
+                .unwrap_or_else(|| state.view.get_allocated_height() as f32 * DEFAULT_PAGE_SIZE_RATIO);
+            (state.scroll_line_size, page_size)
+        };
+        let modifiers = event.get_state();
+        let step =
+            if modifiers.contains(ModifierType::MOD1_MASK) {
+                page_size
+            }
+            else {
+                line_size
+            };
+        // Servo scrolls up for positive deltas, GDK for negative ones.
+        let (dx, dy) =
+            match event.get_direction() {
+                ScrollDirection::Up => (0.0, step),
+                ScrollDirection::Down => (0.0, -step),
+                ScrollDirection::Left => (step, 0.0),
+                ScrollDirection::Right => (-step, 0.0),
+                ScrollDirection::Smooth => {
+                    // The deltas of a touchpad are already proportional to the distance scrolled.
+                    let (dx, dy) = event.get_delta();
+                    (-dx as f32 * line_size, -dy as f32 * line_size)
+                },
+                _ => return,
+            };
+        // Like in the other browsers, Shift turns the steps of a wheel into horizontal ones.
+        let (dx, dy) =
+            if modifiers.contains(ModifierType::SHIFT_MASK) && event.get_direction() != ScrollDirection::Smooth {
+                (dy, dx)
+            }
+            else {
+                (dx, dy)
+            };
+
+        // Any new scroll stops the kinetic scrolling.
+        self.state.borrow_mut().kinetic_generation += 1;
+
+        if let ScrollDirection::Smooth = event.get_direction() {
+            let scrolling = self.state.borrow().scrolling;
+            if event.get_is_stop() {
+                // The fingers left the touchpad: keep scrolling with the last velocity.
+                self.state.borrow_mut().scrolling = false;
+                let velocity = self.state.borrow().scroll_velocity;
+                self.kinetic_scroll(velocity);
+            }
+            else if !scrolling {
+                self.state.borrow_mut().scrolling = true;
+                self.state.borrow_mut().scroll_velocity = (dx, dy);
+                self.send_scroll(dx, dy, TouchEventType::Down);
+            }
+            else {
+                self.state.borrow_mut().scroll_velocity = (dx, dy);
+                self.send_scroll(dx, dy, TouchEventType::Move);
+            }
+        }
+        else {
+            self.send_scroll(dx, dy, TouchEventType::Move);
+        }
+    }
+
+    /// Select all the content of the focused text field.
+    pub fn select_all(&self) {
+        self.send_shortcut('a', Code::KeyA);
+    }
+
+    fn send_scroll(&self, dx: f32, dy: f32, phase: TouchEventType) {
+        let pointer = {
+            let pointer = &self.state.borrow().pointer;
+            TypedPoint2D::new(pointer.x as i32, pointer.y as i32)
+        };
+        let scroll_location = ScrollLocation::Delta(TypedVector2D::new(dx, dy));
+        self.handle_events(vec![WindowEvent::Scroll(scroll_location, pointer, phase)]);
+    }
+
+    /// Servo has no editing commands: send the keyboard shortcuts instead.
+    fn send_shortcut(&self, char: char, code: Code) {
+        let down = KeyboardEvent {
+            state: KeyState::Down,
+            key: Key::Character(char.to_string()),
+            code,
+            modifiers: Modifiers::CONTROL,
+            ..KeyboardEvent::default()
+        };
+        let up = KeyboardEvent {
+            state: KeyState::Up,
+            ..down.clone()
+        };
+        with_browser!(self, |_browser_id| {
+            self.handle_events(vec![WindowEvent::Keyboard(down), WindowEvent::Keyboard(up)]);
         });
     }
 
+    fn set_cursor(&self, cursor: CursorKind) {
+        let callback = self.state.borrow().cursor_changed_callback.clone();
+        if let Some(callback) = callback {
+            if let Inhibit(true) = callback(cursor) {
+                return;
+            }
+        }
+        let mut state = self.state.borrow_mut();
+        let window =
+            match state.view.get_window() {
+                Some(window) => window,
+                None => return,
+            };
+        let name = convert::cursor_name(cursor);
+        if !state.cursors.contains_key(name) {
+            let display = state.view.get_display().or_else(Display::get_default);
+            if let Some(cursor) = display.and_then(|display| Cursor::new_from_name(&display, name)) {
+                state.cursors.insert(name, cursor);
+            }
+        }
+        // Fall back to the cursor of the parent window for the names the theme does not have.
+        window.set_cursor(state.cursors.get(name));
+    }
+
+    /// Set whether the back and forward buttons of the mouse navigate in the history.
+    pub fn set_navigation_buttons(&self, enabled: bool) {
+        self.state.borrow_mut().navigation_buttons = enabled;
+    }
+
+    /// Set the distance scrolled by a wheel step, in pixels (default: 38).
+    pub fn set_scroll_line_size(&self, size: f32) {
+        self.state.borrow_mut().scroll_line_size = size;
+    }
+
+    /// Set the distance scrolled by a wheel step with Alt held, in pixels (default: most of the
+    /// height of the view).
+    pub fn set_scroll_page_size(&self, size: f32) {
+        self.state.borrow_mut().scroll_page_size = Some(size);
+    }
+
+    /// Servo does not report where the caret of the focused input is, so the candidate window is
+    /// placed where the user clicked last.
+    fn show_ime(&self) {
+        let state = self.state.borrow();
+        let area = Rectangle {
+            x: state.last_click.x as i32,
+            y: state.last_click.y as i32,
+            width: 1,
+            height: 1,
+        };
+        state.im_context.set_cursor_location(&area);
+        state.im_context.focus_in();
+    }
+
+    /// Whether the navigation to `url` comes from load_internal() rather than from the page.
+    fn take_internal_load(&self, url: &ServoUrl) -> bool {
+        let hash = url_hash(url.as_str());
+        let mut state = self.state.borrow_mut();
+        match state.internal_loads.iter().position(|&internal_hash| internal_hash == hash) {
+            Some(index) => {
+                state.internal_loads.remove(index);
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Take the callback of the script `id`, if `token` is the one of its report.
+    fn take_script_callback(&self, id: u64, token: &str) -> Option<Rc<Fn(Result<Value, Error>)>> {
+        let mut state = self.state.borrow_mut();
+        match state.script_callbacks.get(&id) {
+            Some(&(ref expected_token, _)) if expected_token == token => (),
+            // Forged by the page.
+            _ => return None,
+        }
+        state.script_callbacks.remove(&id).map(|(_, callback)| callback)
+    }
+
+    /// Whether a LoadStart comes from a script run by run_in_page() rather than from a navigation.
+    fn take_script_load(&self) -> bool {
+        let mut state = self.state.borrow_mut();
+        if state.script_loads == 0 {
+            return false;
+        }
+        state.script_loads -= 1;
+        true
+    }
+
+    fn title_changed(&self, title: Option<String>) {
+        let callback = {
+            let mut state = self.state.borrow_mut();
+            state.title = title.clone();
+            state.title_callback.clone()
+        };
+        if let Some(callback) = callback {
+            callback(title);
+        }
+    }
+
+    fn touch_event(&self, event: &Event) {
+        let phase =
+            match event.get_event_type() {
+                EventType::TouchBegin => TouchEventType::Down,
+                EventType::TouchUpdate => TouchEventType::Move,
+                EventType::TouchEnd => TouchEventType::Up,
+                EventType::TouchCancel => TouchEventType::Cancel,
+                _ => return,
+            };
+        let event =
+            match event.clone().downcast::<EventTouch>() {
+                Ok(event) => event,
+                Err(_) => return,
+            };
+        let sequence =
+            match event.get_event_sequence() {
+                Some(sequence) => sequence,
+                None => return,
+            };
+        let (x, y) = event.get_position();
+        let touch_id = {
+            let mut state = self.state.borrow_mut();
+            if state.pinching {
+                return;
+            }
+            let index = state.touches.iter().position(|&(ref touch, ..)| *touch == sequence);
+            match (index, phase) {
+                (Some(index), TouchEventType::Up) | (Some(index), TouchEventType::Cancel) =>
+                    state.touches.remove(index).1,
+                (Some(index), _) => {
+                    state.touches[index].2 = Pos::new(x, y);
+                    state.touches[index].1
+                },
+                (None, TouchEventType::Down) => {
+                    let touch_id = TouchId(state.next_touch_id);
+                    state.next_touch_id = state.next_touch_id.wrapping_add(1);
+                    state.touches.push((sequence, touch_id, Pos::new(x, y)));
+                    touch_id
+                },
+                // A sequence that started before the view was realized, or that was part of a pinch.
+                (None, _) => return,
+            }
+        };
+        self.handle_events(vec![WindowEvent::Touch(phase, touch_id, TypedPoint2D::new(x as f32, y as f32))]);
+    }
+
+    pub fn unregister_message_handler(&self, name: &str) {
+        self.state.borrow_mut().message_handlers.remove(name);
+    }
+
     pub fn zoom(&self, step: f32) {
         let step = step + 1.0;
         {
             let state = self.state.borrow();
             state.zoom_level.set(state.zoom_level.get() * step);
         }
-        with_servo!(self, |_browser_id, servo| {
-            servo.handle_events(vec![WindowEvent::Zoom(step)]);
+        with_browser!(self, |_browser_id| {
+            self.handle_events(vec![WindowEvent::Zoom(step)]);
         });
     }
 
@@ -424,3 +1908,17 @@ impl WebView {
         self.state.borrow().view.clone()
     }
 }
+
+fn url_hash(url: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    url.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Whether the event is a pointer event GDK emulates for a touch screen: Servo already gets the
+/// touch events and synthesizes the clicks itself.
+fn is_touch_emulated(event: &Event) -> bool {
+    event.get_source_device()
+        .map(|device| device.get_source() == InputSource::Touchscreen)
+        .unwrap_or(false)
+}
@@ -12,11 +12,9 @@ use servo::euclid::{Point2D, ScaleFactor, Size2D, TypedPoint2D, TypedRect, Typed
 use servo::gl;
 use servo::ipc_channel::ipc;
 use servo::net_traits::net_error_list::NetError;
-use servo::script_traits::LoadData;
 use servo::servo_config::opts;
 use servo::servo_geometry::DeviceIndependentPixel;
 use servo::servo_url::ServoUrl;
-use servo::style_traits::cursor::CursorKind;
 use servo::style_traits::DevicePixel;
 use servo::webrender_api::DeviceUintRect;
 
@@ -31,14 +29,8 @@ struct Allocation {
 
 pub struct GtkWindow {
     animation_state: Cell<AnimationState>,
-    can_go_back: Cell<bool>,
-    can_go_forward: Cell<bool>,
     gl: Rc<gl::Gl>,
-    title: RefCell<Option<String>>,
-    title_callback: RefCell<Option<Box<Fn(Option<String>)>>>,
-    url: RefCell<Option<String>>,
-    url_callback: RefCell<Option<Box<Fn(String)>>>,
-    view: View,
+    view: RefCell<View>,
     waker: Box<EventLoopWaker>,
 }
 
@@ -46,20 +38,14 @@ impl GtkWindow {
     pub fn new(gl: Rc<gl::Gl>, view: View, waker: Box<EventLoopWaker>) -> Self {
         GtkWindow {
             animation_state: Cell::new(AnimationState::Idle),
-            can_go_back: Cell::new(false),
-            can_go_forward: Cell::new(false),
             gl,
-            title: RefCell::new(None),
-            title_callback: RefCell::new(None),
-            url: RefCell::new(None),
-            url_callback: RefCell::new(None),
-            view,
+            view: RefCell::new(view),
             waker,
         }
     }
 
     fn device_hidpi_factor(&self) -> TypedScale<f32, DeviceIndependentPixel, DevicePixel> {
-        TypedScale::new(self.view.get_scale_factor() as f32)
+        TypedScale::new(self.view.borrow().get_scale_factor() as f32)
     }
 
     fn servo_hidpi_factor(&self) -> TypedScale<f32, DeviceIndependentPixel, DevicePixel> {
@@ -72,24 +58,8 @@ impl GtkWindow {
         }
     }
 
-    pub fn can_go_back(&self) -> bool {
-        self.can_go_back.get()
-    }
-
-    pub fn can_go_forward(&self) -> bool {
-        self.can_go_forward.get()
-    }
-
-    pub fn connect_title_changed<F: Fn(Option<String>) + 'static>(&self, callback: F) {
-        *self.title_callback.borrow_mut() = Some(Box::new(callback));
-    }
-
-    pub fn connect_url_changed<F: Fn(String) + 'static>(&self, callback: F) {
-        *self.url_callback.borrow_mut() = Some(Box::new(callback));
-    }
-
     fn get_geometry(&self) -> Allocation {
-        let allocation = self.view.get_allocation();
+        let allocation = self.view.borrow().get_allocation();
         let (mut width, mut height) = (allocation.width as u32, allocation.height as u32);
 
         #[cfg(target_os = "windows")]
@@ -111,18 +81,15 @@ impl GtkWindow {
         }
     }
 
-    pub fn get_title(&self) -> Option<String> {
-        self.title.borrow().clone()
-    }
-
-    pub fn get_url(&self) -> Option<String> {
-        self.url.borrow().clone()
+    /// Set the view the compositor paints into.
+    pub fn set_view(&self, view: View) {
+        *self.view.borrow_mut() = view;
     }
 }
 
 impl WindowMethods for GtkWindow {
     fn prepare_for_composite(&self) -> bool {
-        self.view.make_current();
+        self.view.borrow().make_current();
         true
     }
 
@@ -131,14 +98,10 @@ impl WindowMethods for GtkWindow {
     }
 
     fn present(&self) {
-        self.view.queue_render();
+        self.view.borrow().queue_render();
     }
 
     // TODO: cleanup deleted methods.
-    /*fn supports_clipboard(&self) -> bool {
-        false
-    }*/
-
     fn create_event_loop_waker(&self) -> Box<EventLoopWaker> {
         self.waker.clone()
     }
@@ -195,24 +158,7 @@ impl WindowMethods for GtkWindow {
             Point2D::new(geometry.x as i32, geometry.y as i32))
     }*/
 
-    /*fn set_page_title(&self, _id: BrowserId, title: Option<String>) {
-        *self.title.borrow_mut() = title.clone();
-        if let Some(ref callback) = *self.title_callback.borrow() {
-            callback(title);
-        }
-    }*/
-
-    /*fn allow_navigation(&self, _id: BrowserId, _url: ServoUrl, chan: ipc::IpcSender<bool>) {
-        chan.send(true).ok();
-    }
-
-    fn set_inner_size(&self, _id: BrowserId, _size: Size2D<u32>) {
-    }*/
-
-    /*fn set_position(&self, _id: BrowserId, _point: Point2D<i32>) {
-    }
-
-    fn set_fullscreen_state(&self, _id: BrowserId, _state: bool) {
+    /*fn set_fullscreen_state(&self, _id: BrowserId, _state: bool) {
     }
 
     fn status(&self, _id: BrowserId, _status: Option<String>) {
@@ -228,64 +174,9 @@ impl WindowMethods for GtkWindow {
     }
 
     fn head_parsed(&self, _id: BrowserId) {
-    }
-
-    fn history_changed(&self, _id: BrowserId, entries: Vec<LoadData>, current: usize) {
-        self.can_go_back.set(!entries.is_empty() && current > 0);
-        self.can_go_forward.set(!entries.is_empty() && current < entries.len() - 1);
-        let url = &entries[current].url;
-        let url = url.as_str().to_string();
-        *self.url.borrow_mut() = Some(url.clone());
-        if let Some(ref callback) = *self.url_callback.borrow() {
-            callback(url);
-        }
     }*/
 
-    /*fn set_cursor(&self, cursor: CursorKind) {
-        let cursor_name = match cursor {
-            CursorKind::None => "none",
-            CursorKind::Default => "default",
-            CursorKind::Pointer => "pointer",
-            CursorKind::ContextMenu => "context-menu",
-            CursorKind::Help => "help",
-            CursorKind::Progress => "progress",
-            CursorKind::Wait => "wait",
-            CursorKind::Cell => "cell",
-            CursorKind::Crosshair => "crosshair",
-            CursorKind::Text => "text",
-            CursorKind::VerticalText => "vertical-text",
-            CursorKind::Alias => "alias",
-            CursorKind::Copy => "copy",
-            CursorKind::Move => "move",
-            CursorKind::NoDrop => "no-drop",
-            CursorKind::NotAllowed => "not-allowed",
-            CursorKind::Grab => "grab",
-            CursorKind::Grabbing => "grabbing",
-            CursorKind::EResize => "e-resize",
-            CursorKind::NResize => "n-resize",
-            CursorKind::NeResize => "ne-resize",
-            CursorKind::NwResize => "nw-resize",
-            CursorKind::SResize => "s-resize",
-            CursorKind::SeResize => "se-resize",
-            CursorKind::SwResize => "sw-resize",
-            CursorKind::WResize => "w-resize",
-            CursorKind::EwResize => "ew-resize",
-            CursorKind::NsResize => "ns-resize",
-            CursorKind::NeswResize => "nesw-resize",
-            CursorKind::NwseResize => "nwse-resize",
-            CursorKind::ColResize => "col-resize",
-            CursorKind::RowResize => "row-resize",
-            CursorKind::AllScroll => "all-scroll",
-            CursorKind::ZoomIn => "zoom-in",
-            CursorKind::ZoomOut => "zoom-out",
-        };
-        let display = Display::get_default().unwrap();
-        let cursor = gdk::Cursor::new_from_name(&display, cursor_name);
-        let window = self.view.get_window().unwrap();
-        window.set_cursor(&cursor);
-    }
-
-    fn set_favicon(&self, _id: BrowserId, _url: ServoUrl) {
+    /*fn set_favicon(&self, _id: BrowserId, _url: ServoUrl) {
     }
 
     fn handle_key(&self, _id: Option<BrowserId>, _ch: Option<char>, _key: Key, _mods: Modifiers) {